@@ -1,21 +1,52 @@
 #![allow(clippy::single_match)]
 #![allow(clippy::type_complexity)]
 
+use crossterm::event::KeyEvent;
+use log::Level;
 use notify_rust::Notification;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
-use crate::custom_widgets::StatefulList;
+use crate::backup::{self, ImportMode};
+use crate::command::{self, CommandLine};
+use crate::custom_widgets::{StatefulList, TextInput};
+use crate::form::{FieldKind, FieldSpec, Form};
+use crate::history::{self, PomodoroRecord};
+use crate::hooks::{HookContext, HookEvent, HookTask, Hooks};
+use crate::logging::LogEntry;
+use crate::mpris::Mpris;
+use crate::presets::{self, Preset, PresetLengths, TimerOverride};
+use crate::service::{RemoteCommand, TimerStatus};
+use crate::storage::{SavedData, SavedSettings};
 use psimple::Simple;
 use pulse::sample::{Format, Spec};
 use pulse::stream::Direction;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::read;
 
 const SECS_PER_MINUTE: u64 = 60;
+const RINGTONE_PATH: &str = "./resources/achivement-bell.wav";
 const DEFAULT_POMODORO_LENGTH: u64 = 25;
 const DEFAULT_SHORT_BREAK_LENGTH: u64 = 5;
 const DEFAULT_LONG_BREAK_LENGTH: u64 = 15;
 const DEFAULT_POMODORO_PER_LONG_BREAK: u64 = 4;
 const DEFAULT_POMODORO_PER_TASK: u64 = 1;
+const TASK_TITLE_MAX_LEN: usize = 64;
+pub const TASK_ESTIMATE_MAX: u64 = 99;
+// NOTE enough for a preset name and a few settings
+const TASK_TIMER_MAX_LEN: usize = 96;
+const TASK_NOTES_MAX_LINES: usize = 100;
+const TASK_NOTES_MAX_LINE_LEN: usize = 256;
+// NOTE how late after the shown second changes the main loop wakes up
+const TICK_MARGIN: Duration = Duration::from_millis(5);
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+// NOTE older toasts go away early past this
+const TOAST_LIMIT: usize = 3;
+// NOTE oldest changes are forgotten past this
+const UNDO_LIMIT: usize = 100;
 
 pub type AppAction = fn(&mut App);
 
@@ -26,6 +57,35 @@ pub enum State {
     LongBreak,
 }
 
+impl State {
+    /// NOTE the phase coming after this one, shared by timer finishing and timer skipping
+    pub fn next(&self, pomodoro_per_long_break: u64) -> Self {
+        match *self {
+            State::Pomodoro(0) => State::LongBreak,
+            State::Pomodoro(x) => State::ShortBreak(x - 1),
+            State::ShortBreak(x) => State::Pomodoro(x),
+            State::LongBreak => State::Pomodoro(pomodoro_per_long_break),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::Pomodoro(_) => "Pomodoro",
+            State::ShortBreak(_) => "Short Break",
+            State::LongBreak => "Long Break",
+        }
+    }
+
+    /// NOTE for scripts and config files, same as the setting of its length
+    pub fn id(&self) -> &'static str {
+        match self {
+            State::Pomodoro(_) => "pomodoro",
+            State::ShortBreak(_) => "short_break",
+            State::LongBreak => "long_break",
+        }
+    }
+}
+
 impl Default for State {
     fn default() -> Self {
         if cfg!(debug_assertions) {
@@ -42,25 +102,166 @@ pub struct App {
     timer: Option<Timer>,
     // NOTE pomodoro current loop state
     pub state: State,
+    pub mode: Mode,
+    // NOTE count typed before a command in Normal or Visual mode, e.g. `3` in `3j`
+    pub count: Option<usize>,
+    // NOTE keys of a key sequence typed so far, e.g. the first `d` in `dd`
+    pub pending_keys: Vec<KeyEvent>,
+    // NOTE when the last key of a pending key sequence was typed
+    pub pending_since: Option<Instant>,
+    // NOTE where Visual mode selection started in task list
+    pub visual_anchor: Option<usize>,
+    // NOTE tasks yanked by `yy` or in Visual mode, pasted by `p`
+    pub register: Vec<Task>,
+    pub command_line: CommandLine,
+    // NOTE result of last command, shown in status line
+    pub message: Option<String>,
+    pub theme: Theme,
     pub tab_selected: Tabs,
     // NOTE Tab1: Pomodoro Settings DONE
     pub timer_setting: TimerSetting,
-    pub timer_setting_input: Input, // NOTE TimerSetting input fields
+    pub timer_setting_input: Form,
     pub state_setting: StateSetting,
-    // HACK use proc macro to generate input fields receiver struct and implementation
-    pub task_manager_input: Input1, // NOTE TimerSetting input fields
+    pub task_manager_input: Form,
+    // NOTE pane receiving navigation keys in Normal mode
+    pub focus: Focus,
     pub task_list: StatefulList<Task>,
+    // NOTE done tasks moved out of task list, still counted in statistics
+    pub archived_tasks: Vec<Task>,
+    // NOTE changes to task list undo can take back, newest last
+    undo_stack: Vec<UndoEntry>,
+    // NOTE tasks fitting in task list pane, updated when rendering
+    pub task_list_page_size: usize,
+    // NOTE the task the next pomodoro counts against
+    pub active_task_id: Option<u64>,
+    // NOTE task loaded into task manager fields, saved back on confirm instead of added
+    pub editing_task_id: Option<u64>,
+    // NOTE notes to open in external editor, taken by main loop
+    pub notes_editor_request: Option<NotesTarget>,
+    // NOTE main loop hands terminal back to the shell and stops the process
+    pub suspend_requested: bool,
+    next_task_id: u64,
     pub should_quit: bool,
     pub page_selected: Page,
+    // NOTE first line of help page shown on screen, clamped when rendering
+    pub help_scroll: u16,
+    // NOTE lines of help page fitting on screen, updated when rendering
+    pub help_page_size: u16,
+    // NOTE problems found in keybindings file at startup, listed in help page
+    pub keybinding_problems: Vec<String>,
+    // NOTE errors shown over the top right corner, oldest first
+    pub toasts: Vec<Toast>,
+    // NOTE built-in presets first, then user ones
+    pub presets: Vec<Preset>,
+    // NOTE user commands run on phase changes
+    pub hooks: Hooks,
+    // NOTE logged since the app started, with or without an active task
+    pub session_interruptions: InterruptionCounts,
+    // NOTE logged during the pomodoro running now, go into its record
+    pomodoro_interruptions: InterruptionCounts,
+    // NOTE pomodoros that ended, oldest first
+    pub history: Vec<PomodoroRecord>,
+    // NOTE media players told about phase starts, None without mpris file
+    pub mpris: Option<Mpris>,
+    pub layout: ScreenLayout,
+}
+
+#[derive(Debug)]
+pub struct Toast {
+    pub text: String,
+    until: Instant,
+}
+
+/// NOTE where clickable parts were drawn in the last frame, updated when rendering
+#[derive(Debug, Default)]
+pub struct ScreenLayout {
+    // NOTE rows of the task list, without borders and padding
+    pub task_list: Rect,
+    pub clock: Rect,
+    // NOTE name of the other tab in the input panel title
+    pub other_tab: Rect,
+    // NOTE one for each field of the input panel shown
+    pub fields: Vec<Rect>,
+    // NOTE names in preset picker of settings tab, in `App::presets` order
+    pub presets: Vec<Rect>,
+}
+
+/// NOTE notes edited in external editor
+#[derive(Debug, Clone, Copy)]
+pub enum NotesTarget {
+    Task(u64),
+    // NOTE notes field of task manager
+    Field,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+    Command,
+}
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Normal => "NORMAL",
+            Mode::Insert => "INSERT",
+            Mode::Visual => "VISUAL",
+            Mode::Command => "COMMAND",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Default,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const NAMES: [&'static str; 3] = ["default", "dark", "light"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Theme::Default),
+            "dark" => Some(Theme::Dark),
+            "light" => Some(Theme::Light),
+            _ => None,
+        }
+    }
+
+    /// NOTE style every widget is drawn on top of
+    pub fn base_style(self) -> Style {
+        match self {
+            Theme::Default => Style::default(),
+            Theme::Dark => Style::default().fg(Color::White).bg(Color::Black),
+            Theme::Light => Style::default().fg(Color::Black).bg(Color::White),
+        }
+    }
 }
 
 #[derive(Default, Debug)]
 pub enum Page {
     Help,
+    // NOTE scrolls with help page keys
+    Log,
+    // NOTE pomodoro history summed up, scrolls like log page
+    Stats,
     #[default]
     Normal,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Focus {
+    #[default]
+    TaskList,
+    Console,
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 pub enum Tabs {
     #[default]
@@ -79,45 +280,55 @@ impl Tabs {
 
 impl Default for App {
     fn default() -> Self {
+        let mut app = App {
+            timer_setting: TimerSetting::default(),
+            timer_setting_input: timer_setting_form(),
+            timer: None,
+            state_setting: StateSetting::default(),
+            state: State::default(),
+            mode: Mode::default(),
+            count: None,
+            pending_keys: Vec::new(),
+            pending_since: None,
+            visual_anchor: None,
+            register: Vec::new(),
+            command_line: CommandLine::default(),
+            message: None,
+            theme: Theme::default(),
+            task_manager_input: Form::new(&TASK_FIELDS),
+            tab_selected: Tabs::default(),
+            focus: Focus::default(),
+            task_list: StatefulList::default(),
+            task_list_page_size: 0,
+            active_task_id: None,
+            editing_task_id: None,
+            notes_editor_request: None,
+            suspend_requested: false,
+            archived_tasks: Vec::new(),
+            undo_stack: Vec::new(),
+            next_task_id: 1,
+            should_quit: false,
+            page_selected: Page::default(),
+            help_scroll: 0,
+            help_page_size: 0,
+            keybinding_problems: Vec::new(),
+            toasts: Vec::new(),
+            presets: Vec::new(),
+            hooks: Hooks::default(),
+            session_interruptions: InterruptionCounts::default(),
+            pomodoro_interruptions: InterruptionCounts::default(),
+            history: Vec::new(),
+            mpris: None,
+            layout: ScreenLayout::default(),
+        };
         if cfg!(debug_assertions) {
-            let task1 = Task {
-                title: "Pomodoro Timer Dev".to_string(),
-                notes: String::new(),
-                pomodoros: 2,
-            };
-            let task2 = Task {
-                title: "Renpy GalGame Dev".to_string(),
-                notes: String::new(),
-                pomodoros: 2,
-            };
-            let task_list = vec![Task::default(), task1, task2];
-            let task_list = StatefulList::with_items(task_list);
-            App {
-                timer_setting: TimerSetting::default(),
-                timer_setting_input: Input::default(),
-                timer: None,
-                state_setting: StateSetting::default(),
-                state: State::default(),
-                task_manager_input: Input1::default(),
-                tab_selected: Tabs::default(),
-                task_list,
-                should_quit: false,
-                page_selected: Page::default(),
-            }
-        } else {
-            App {
-                timer_setting: TimerSetting::default(),
-                timer_setting_input: Input::default(),
-                timer: None,
-                state_setting: StateSetting::default(),
-                state: State::default(),
-                task_manager_input: Input1::default(),
-                tab_selected: Tabs::default(),
-                task_list: StatefulList::default(),
-                should_quit: false,
-                page_selected: Page::default(),
-            }
+            let task = Task::default();
+            app.push_task(task.title(), &task.notes, task.pomodoros);
+            app.push_task("Pomodoro Timer Dev", "", 2);
+            app.push_task("Renpy GalGame Dev", "", 2);
+            app.task_list.state.select(Some(0));
         }
+        app
     }
 }
 
@@ -127,11 +338,75 @@ pub struct StateSetting {
 }
 
 /// HACK task start time, end time for future statistics feature
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
+    // NOTE stays the same while the task moves around in task list
+    pub id: u64,
     title: String,
     pub notes: String,
     pub pomodoros: u64,
+    // NOTE pomodoros finished while this task is active
+    pub completed: u64,
+    // NOTE set by hand or when completed pomodoros reach the estimate
+    pub done: bool,
+    // NOTE applies in place of timer settings while this task is active
+    #[serde(default, skip_serializing_if = "TimerOverride::is_empty")]
+    pub timer: TimerOverride,
+    // NOTE logged during pomodoros while this task is active, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interruptions: Vec<Interruption>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterruptionKind {
+    // NOTE an urge of one's own, e.g. checking mail
+    Internal,
+    // NOTE someone or something else, e.g. a phone call
+    External,
+}
+
+impl InterruptionKind {
+    pub const NAMES: [&'static str; 2] = ["internal", "external"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "internal" => Some(InterruptionKind::Internal),
+            "external" => Some(InterruptionKind::External),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InterruptionKind::Internal => "internal",
+            InterruptionKind::External => "external",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interruption {
+    pub kind: InterruptionKind,
+    // NOTE RFC 3339, local time
+    pub time: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterruptionCounts {
+    pub internal: u64,
+    pub external: u64,
+}
+
+impl InterruptionCounts {
+    fn add(&mut self, kind: InterruptionKind) {
+        match kind {
+            InterruptionKind::Internal => self.internal += 1,
+            InterruptionKind::External => self.external += 1,
+        }
+    }
 }
 
 impl Default for Task {
@@ -142,9 +417,14 @@ impl Default for Task {
             (String::new(), String::new())
         };
         Self {
+            id: 0,
             title,
             notes,
             pomodoros: DEFAULT_POMODORO_PER_TASK,
+            completed: 0,
+            done: false,
+            timer: TimerOverride::default(),
+            interruptions: Vec::new(),
         }
     }
 }
@@ -153,6 +433,118 @@ impl Task {
     pub fn title(&self) -> &str {
         &self.title
     }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    /// NOTE same content, ids aside
+    fn same_as(&self, other: &Task) -> bool {
+        self.title == other.title
+            && self.notes == other.notes
+            && self.pomodoros == other.pomodoros
+            && self.completed == other.completed
+            && self.done == other.done
+            && self.timer == other.timer
+            && self.interruptions == other.interruptions
+    }
+
+    pub fn pomodoros_left(&self) -> u64 {
+        self.pomodoros.saturating_sub(self.completed)
+    }
+
+    fn progress(&self) -> Progress {
+        Progress {
+            id: self.id,
+            completed: self.completed,
+            interruptions: self.interruptions.len(),
+        }
+    }
+
+    /// NOTE fields an edit can change, as they are now
+    fn edited(&self) -> Change {
+        Change::Edited {
+            id: self.id,
+            title: self.title.clone(),
+            notes: self.notes.clone(),
+            pomodoros: self.pomodoros,
+            timer: self.timer.clone(),
+        }
+    }
+
+    pub fn interruption_counts(&self) -> InterruptionCounts {
+        let mut counts = InterruptionCounts::default();
+        for interruption in self.interruptions.iter() {
+            counts.add(interruption.kind);
+        }
+        counts
+    }
+}
+
+/// NOTE what the timer counted on a task, by its id
+#[derive(Debug, PartialEq, Eq)]
+struct Progress {
+    id: u64,
+    completed: u64,
+    interruptions: usize,
+}
+
+/// NOTE a change to task list, recorded so undo can take back just that change.
+/// Completed pomodoros and interruptions belong to the timer and are never taken back,
+/// nor thrown away with tasks that got them after being added
+#[derive(Debug)]
+enum Change {
+    // NOTE tasks taken out of task list, by the index each one had, lowest first
+    Removed {
+        tasks: Vec<(usize, Task)>,
+        // NOTE set when the active task was among them
+        active_task_id: Option<u64>,
+    },
+    // NOTE tasks put into task list or archive, and history records by their end time
+    Added {
+        tasks: Vec<Progress>,
+        records: Vec<String>,
+    },
+    // NOTE fields as they were before an edit
+    Edited {
+        id: u64,
+        title: String,
+        notes: String,
+        pomodoros: u64,
+        timer: TimerOverride,
+    },
+    Done {
+        id: u64,
+        done: bool,
+    },
+    Moved {
+        id: u64,
+        from: usize,
+    },
+    // NOTE tasks moved to archive, by the index each one had, lowest first
+    Archived {
+        tasks: Vec<(usize, u64)>,
+        active_task_id: Option<u64>,
+    },
+    // NOTE everything an import replaced, none of it could be touched by the timer since,
+    // and the tasks it brought
+    Replaced {
+        tasks: Vec<Task>,
+        archived_tasks: Vec<Task>,
+        active_task_id: Option<u64>,
+        next_task_id: u64,
+        imported: Vec<Progress>,
+        history: Vec<PomodoroRecord>,
+        // NOTE records the import brought, the ones after them were recorded since
+        imported_history: usize,
+    },
+}
+
+#[derive(Debug)]
+struct UndoEntry {
+    name: &'static str,
+    selected: Option<usize>,
+    change: Change,
 }
 
 impl Default for StateSetting {
@@ -167,7 +559,6 @@ impl Default for StateSetting {
 pub struct Timer {
     total_time: Duration,
     launch_timer: Instant,
-    /// HACK for future statistics feature
     time_passed: Duration,
     pause_timer: Option<Instant>,
     time_pause: Duration,
@@ -189,6 +580,11 @@ impl Timer {
         self.total_time.saturating_sub(self.time_passed)
     }
 
+    /// NOTE pauses left out
+    pub fn time_passed(&self) -> Duration {
+        self.time_passed.min(self.total_time)
+    }
+
     pub fn pause(&mut self) {
         match self.pause_timer {
             Some(_) => {}
@@ -221,177 +617,146 @@ impl Timer {
     pub fn is_paused(&self) -> bool {
         self.pause_timer.is_some()
     }
-}
-
-/// HACK generate corresponding input fields from settings needed
-/// HACK Stateful List Like implementation
-#[derive(Debug)]
-pub struct Input {
-    timer: String,
-    short_break: String,
-    long_break: String,
-    pomodoro_per_long_break: String,
-    field_selected: InputField,
-}
-
-#[derive(Clone, Copy, Debug)]
-enum InputField {
-    Timer,
-    ShortBreak,
-    LongBreak,
-    PomodoroPerLongBreak,
-}
-
-#[derive(Debug, Default)]
-pub struct Input1 {
-    task_name: String,
-    task_notes: String,
-    pomodoros_per_task: String,
-    field_selected: InputField1,
-}
-
-impl Input1 {
-    pub fn display(&self) -> ((&str, &str), (&str, &str), (&str, &str)) {
-        // HACK refactor this bullshit type
-        let (s1, s2, s3) = match self.field_selected {
-            InputField1::TaskName => (">> Task Name: ", "Est Pomodoros: ", "Task Notes:"),
-            InputField1::PomodorosPerTask => ("Task Name: ", ">> Est Pomodoros: ", "Task Notes:"),
-            InputField1::TaskNotes => ("Task Name: ", "Est Pomodoros: ", ">> Task Notes:"),
-        };
-        (
-            (s1, &self.task_name),
-            (s2, &self.pomodoros_per_task),
-            (s3, &self.task_notes),
-        )
-    }
 
-    pub fn get_field_mut(&mut self) -> &mut String {
-        match self.field_selected {
-            InputField1::TaskNotes => &mut self.task_notes,
-            InputField1::PomodorosPerTask => &mut self.pomodoros_per_task,
-            InputField1::TaskName => &mut self.task_name,
+    /// NOTE time until the second shown changes, None while paused
+    pub fn until_next_second(&self) -> Option<Duration> {
+        if self.is_paused() {
+            return None;
         }
-    }
-
-    pub fn select_prev_field(&mut self) {
-        self.field_selected = self.field_selected.prev_field();
-    }
-    pub fn select_next_field(&mut self) {
-        self.field_selected = self.field_selected.next_field();
+        let passed = self.launch_timer.elapsed().saturating_sub(self.time_pause);
+        let left = self.total_time.saturating_sub(passed);
+        // NOTE a little past the change, so it has happened once woken up
+        Some(Duration::from_nanos(left.subsec_nanos() as u64) + TICK_MARGIN)
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
-enum InputField1 {
-    #[default]
-    TaskName,
-    TaskNotes,
-    PomodorosPerTask,
-}
-
-impl InputField1 {
-    pub fn next_field(self) -> Self {
-        match self {
-            InputField1::TaskName => InputField1::PomodorosPerTask,
-            InputField1::PomodorosPerTask => InputField1::TaskNotes,
-            InputField1::TaskNotes => InputField1::TaskName,
-        }
-    }
-    pub fn prev_field(self) -> Self {
-        match self {
-            InputField1::TaskName => InputField1::TaskNotes,
-            InputField1::PomodorosPerTask => InputField1::TaskName,
-            InputField1::TaskNotes => InputField1::PomodorosPerTask,
-        }
-    }
-}
+// HACK more reasonable ringtone playback
+fn play_ringtone() -> Result<(), String> {
+    let spec = Spec {
+        format: Format::S16NE,
+        channels: 2,
+        rate: 44100,
+    };
+    assert!(spec.is_valid());
 
-impl InputField {
-    pub fn prev_field(self) -> Self {
-        match self {
-            InputField::Timer => InputField::PomodoroPerLongBreak,
-            InputField::ShortBreak => InputField::Timer,
-            InputField::LongBreak => InputField::ShortBreak,
-            InputField::PomodoroPerLongBreak => InputField::LongBreak,
-        }
-    }
-    pub fn next_field(self) -> Self {
-        match self {
-            InputField::Timer => InputField::ShortBreak,
-            InputField::ShortBreak => InputField::LongBreak,
-            InputField::LongBreak => InputField::PomodoroPerLongBreak,
-            InputField::PomodoroPerLongBreak => InputField::Timer,
-        }
-    }
+    let s = Simple::new(
+        None,                // Use the default server
+        "Pomodoro",          // Our application’s name
+        Direction::Playback, // We want a playback stream
+        None,                // Use the default device
+        "Music",             // Description of our stream
+        &spec,               // Our sample format
+        None,                // Use default channel map
+        None,                // Use default buffering attributes
+    )
+    .map_err(|e| format!("{}", e))?;
+    let buf = read(RINGTONE_PATH).map_err(|e| format!("{}: {}", RINGTONE_PATH, e))?;
+    s.write(&buf).map_err(|e| format!("{}", e))
 }
 
-impl Input {
-    // HACK refactor this bullshit type
-    pub fn display(&self) -> ((&str, &str), (&str, &str), (&str, &str), (&str, &str)) {
-        let (s1, s2, s3, s4) = match self.field_selected {
-            InputField::Timer => (
-                ">> Timer Length: ",
-                "Short Break Length: ",
-                "Long Break Length: ",
-                "Pomodoros Per Long Break: ",
-            ),
-            InputField::ShortBreak => (
-                "Timer Length: ",
-                ">> Short Break Length: ",
-                "Long Break Length: ",
-                "Pomodoros Per Long Break: ",
-            ),
-            InputField::LongBreak => (
-                "Timer Length: ",
-                "Short Break Length: ",
-                ">> Long Break Length: ",
-                "Pomodoros Per Long Break: ",
-            ),
-            InputField::PomodoroPerLongBreak => (
-                "Timer Length: ",
-                "Short Break Length: ",
-                "Long Break Length: ",
-                ">> Pomodoros Per Long Break: ",
-            ),
-        };
-        (
-            (s1, self.timer.as_str()),
-            (s2, self.short_break.as_str()),
-            (s3, self.long_break.as_str()),
-            (s4, self.pomodoro_per_long_break.as_str()),
-        )
-    }
+/// NOTE Pomodoro Settings tab, field names are the ones `:set` takes
+static TIMER_SETTING_FIELDS: [FieldSpec; 4] = [
+    FieldSpec {
+        name: "pomodoro",
+        label: "Timer Length: ",
+        kind: FieldKind::Number {
+            min: 1,
+            max: 99,
+            unit: "min",
+            required: true,
+        },
+    },
+    FieldSpec {
+        name: "short_break",
+        label: "Short Break Length: ",
+        kind: FieldKind::Number {
+            min: 1,
+            max: 99,
+            unit: "min",
+            required: true,
+        },
+    },
+    FieldSpec {
+        name: "long_break",
+        label: "Long Break Length: ",
+        kind: FieldKind::Number {
+            min: 1,
+            max: 99,
+            unit: "min",
+            required: true,
+        },
+    },
+    FieldSpec {
+        name: "pomodoros_per_long_break",
+        label: "Pomodoros Per Long Break: ",
+        kind: FieldKind::Number {
+            min: 1,
+            max: 99,
+            unit: "pomodoros",
+            required: true,
+        },
+    },
+];
 
-    pub fn get_field_mut(&mut self) -> &mut String {
-        match self.field_selected {
-            InputField::Timer => &mut self.timer,
-            InputField::ShortBreak => &mut self.short_break,
-            InputField::LongBreak => &mut self.long_break,
-            InputField::PomodoroPerLongBreak => &mut self.pomodoro_per_long_break,
-        }
-    }
+/// NOTE Task Manager tab
+static TASK_FIELDS: [FieldSpec; 4] = [
+    FieldSpec {
+        name: "title",
+        label: "Task Name: ",
+        kind: FieldKind::Text {
+            max_len: TASK_TITLE_MAX_LEN,
+            required: true,
+        },
+    },
+    FieldSpec {
+        name: "estimate",
+        label: "Est Pomodoros: ",
+        kind: FieldKind::Number {
+            min: 1,
+            max: TASK_ESTIMATE_MAX,
+            unit: "pomodoros",
+            required: false,
+        },
+    },
+    FieldSpec {
+        name: "timer",
+        label: "Timer: ",
+        kind: FieldKind::Text {
+            max_len: TASK_TIMER_MAX_LEN,
+            required: false,
+        },
+    },
+    FieldSpec {
+        name: "notes",
+        label: "Task Notes:",
+        kind: FieldKind::Notes {
+            max_lines: TASK_NOTES_MAX_LINES,
+            max_line_len: TASK_NOTES_MAX_LINE_LEN,
+        },
+    },
+];
 
-    pub fn select_prev_field(&mut self) {
-        self.field_selected = self.field_selected.prev_field();
-    }
-    pub fn select_next_field(&mut self) {
-        self.field_selected = self.field_selected.next_field();
-    }
+/// NOTE one of `TASK_FIELDS`
+fn task_field(name: &str) -> &'static FieldSpec {
+    TASK_FIELDS
+        .iter()
+        .find(|spec| spec.name == name)
+        .expect("no such task field")
 }
 
-impl Default for Input {
-    fn default() -> Self {
-        Self {
-            timer: DEFAULT_POMODORO_LENGTH.to_string(),
-            short_break: DEFAULT_SHORT_BREAK_LENGTH.to_string(),
-            long_break: DEFAULT_LONG_BREAK_LENGTH.to_string(),
-            pomodoro_per_long_break: DEFAULT_POMODORO_PER_LONG_BREAK.to_string(),
-            field_selected: InputField::Timer,
-        }
-    }
+fn timer_setting_form() -> Form {
+    Form::new(&TIMER_SETTING_FIELDS).with_values(&[
+        ("pomodoro", &DEFAULT_POMODORO_LENGTH.to_string()),
+        ("short_break", &DEFAULT_SHORT_BREAK_LENGTH.to_string()),
+        ("long_break", &DEFAULT_LONG_BREAK_LENGTH.to_string()),
+        (
+            "pomodoros_per_long_break",
+            &DEFAULT_POMODORO_PER_LONG_BREAK.to_string(),
+        ),
+    ])
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TimerSetting {
     pub timer: Duration,
     pub short_break: Duration,
@@ -421,12 +786,137 @@ impl App {
         self.tab_selected = self.tab_selected.toggle();
     }
 
-    pub fn clear_input_field(&mut self) {
+    /// NOTE apply an edit to the line keys are typed into,
+    /// the command line in Command mode or the selected input field otherwise
+    fn edit_line<R>(&mut self, edit: impl FnOnce(&mut dyn TextInput) -> R) -> R {
+        if self.mode == Mode::Command {
+            return self.command_line.edit(edit);
+        }
         match self.tab_selected {
-            Tabs::TaskManager => self.task_manager_input.get_field_mut().clear(),
-            Tabs::PomodoroSetting => self.timer_setting_input.get_field_mut().clear(),
+            Tabs::TaskManager => edit(self.task_manager_input.get_field_mut()),
+            Tabs::PomodoroSetting => edit(self.timer_setting_input.get_field_mut()),
+        }
+    }
+
+    pub fn clear_input_field(&mut self) {
+        self.edit_line(|line| line.clear());
+    }
+
+    pub fn cursor_left(&mut self) {
+        self.edit_line(|line| line.move_left());
+    }
+
+    pub fn cursor_right(&mut self) {
+        self.edit_line(|line| line.move_right());
+    }
+
+    pub fn cursor_home(&mut self) {
+        self.edit_line(|line| line.move_home());
+    }
+
+    pub fn cursor_end(&mut self) {
+        self.edit_line(|line| line.move_end());
+    }
+
+    pub fn cursor_word_left(&mut self) {
+        self.edit_line(|line| line.move_word_left());
+    }
+
+    pub fn cursor_word_right(&mut self) {
+        self.edit_line(|line| line.move_word_right());
+    }
+
+    pub fn delete_char_forward(&mut self) {
+        self.edit_line(|line| line.delete_forward());
+    }
+
+    pub fn delete_word_back(&mut self) {
+        self.edit_line(|line| line.delete_word_back());
+    }
+
+    pub fn delete_to_start(&mut self) {
+        self.edit_line(|line| line.delete_to_start());
+    }
+
+    pub fn delete_to_end(&mut self) {
+        self.edit_line(|line| line.delete_to_end());
+    }
+
+    /// NOTE line break in notes, single line fields ignore it
+    pub fn insert_newline(&mut self) {
+        self.edit_line(|line| line.insert_newline());
+    }
+
+    /// NOTE moves between lines of notes, or to the field above from the first line
+    pub fn cursor_up(&mut self) {
+        if !self.edit_line(|line| line.move_up()) {
+            self.select_prev_field();
+        }
+    }
+
+    pub fn cursor_down(&mut self) {
+        if !self.edit_line(|line| line.move_down()) {
+            self.select_next_field();
+        }
+    }
+
+    /// NOTE notes of selected task in Normal mode, notes field in Insert mode,
+    /// the editor runs from main loop as it needs the terminal
+    pub fn edit_notes_in_editor(&mut self) {
+        self.notes_editor_request = match (self.mode, self.tab_selected) {
+            (Mode::Insert, Tabs::TaskManager) => Some(NotesTarget::Field),
+            (Mode::Insert, Tabs::PomodoroSetting) => None,
+            _ => self
+                .task_list
+                .state
+                .selected()
+                .and_then(|idx| self.task_list.items.get(idx))
+                .map(|task| NotesTarget::Task(task.id)),
+        };
+    }
+
+    pub fn notes_text(&self, target: NotesTarget) -> Option<String> {
+        match target {
+            NotesTarget::Field => Some(self.task_manager_input.text("notes")),
+            NotesTarget::Task(id) => self
+                .task_list
+                .items
+                .iter()
+                .find(|task| task.id == id)
+                .map(|task| task.notes.clone()),
+        }
+    }
+
+    /// NOTE notes written in external editor, or why it failed
+    pub fn set_notes(&mut self, target: NotesTarget, notes: Result<String, String>) {
+        let notes = match notes {
+            Ok(notes) => notes,
+            Err(e) => {
+                log::error!("failed to edit notes in editor: {}", e);
+                return;
+            }
+        };
+        match target {
+            NotesTarget::Field => self.task_manager_input.set_text("notes", &notes),
+            NotesTarget::Task(id) => {
+                let Some(idx) = self.task_list.items.iter().position(|task| task.id == id) else {
+                    self.report_error("task was deleted while editing");
+                    return;
+                };
+                if self.task_list.items[idx].notes != notes {
+                    let change = self.task_list.items[idx].edited();
+                    self.checkpoint("edit", change);
+                    self.task_list.items[idx].notes = notes;
+                }
+            }
+        }
+    }
+
+    /// NOTE bracketed paste goes to the line being edited, ignored outside of it
+    pub fn paste_text(&mut self, text: &str) {
+        if matches!(self.mode, Mode::Insert | Mode::Command) {
+            self.edit_line(|line| line.insert_str(text));
         }
-        self.timer_setting_input.get_field_mut().clear();
     }
 
     pub fn select_next_field(&mut self) {
@@ -444,31 +934,32 @@ impl App {
     }
 
     pub fn push_user_input_field(&mut self, c: char) {
-        match self.tab_selected {
-            Tabs::TaskManager => self.task_manager_input.get_field_mut().push(c),
-            Tabs::PomodoroSetting => self.timer_setting_input.get_field_mut().push(c),
-        }
+        self.edit_line(|line| {
+            line.insert(c);
+        });
     }
 
     pub fn pop_user_input_field(&mut self) {
-        match self.tab_selected {
-            Tabs::TaskManager => self.task_manager_input.get_field_mut().pop(),
-            Tabs::PomodoroSetting => self.timer_setting_input.get_field_mut().pop(),
-        };
+        // NOTE backspace on empty command line leaves it, like in Vim
+        if self.mode == Mode::Command && self.command_line.input.is_empty() {
+            self.enter_normal_mode();
+            return;
+        }
+        self.edit_line(|line| {
+            line.delete_back();
+        });
     }
 
     pub fn abort_timer(&mut self) {
         match self.timer {
             Some(_) => {
+                self.run_hook(HookEvent::Skip);
+                if let State::Pomodoro(_) = self.state {
+                    self.record_pomodoro(false);
+                }
                 self.timer = None;
-                match self.state {
-                    State::Pomodoro(0) => self.state = State::LongBreak,
-                    State::Pomodoro(x) => self.state = State::ShortBreak(x - 1),
-                    State::ShortBreak(x) => self.state = State::Pomodoro(x),
-                    State::LongBreak => {
-                        self.state = State::Pomodoro(self.state_setting.pomodoro_per_long_break)
-                    }
-                };
+                self.state = self.state.next(self.effective_settings().1);
+                self.phase_started();
             }
             None => {}
         };
@@ -479,8 +970,10 @@ impl App {
             Some(ref mut timer) => {
                 if timer.is_paused() {
                     timer.resume();
+                    self.run_hook(HookEvent::Resume);
                 } else {
                     timer.pause();
+                    self.run_hook(HookEvent::Pause);
                 }
             }
             None => self.launch_timer(),
@@ -489,19 +982,46 @@ impl App {
 
     pub fn pause_timer(&mut self) {
         if let Some(ref mut timer) = self.timer {
-            timer.pause();
+            if !timer.is_paused() {
+                timer.pause();
+                self.run_hook(HookEvent::Pause);
+            }
         } // do nothing when no timer is running
     }
 
+    /// NOTE players follow the phase once it is up next, launching it tells them again in case
+    /// it is the first one
+    fn phase_started(&self) {
+        if let Some(ref mpris) = self.mpris {
+            mpris.phase_started(self.state.id());
+        }
+    }
+
+    /// NOTE the pomodoro ending now goes into history, `finished` is false when it was skipped
+    fn record_pomodoro(&mut self, finished: bool) {
+        let Some(ref timer) = self.timer else {
+            return;
+        };
+        let counts = std::mem::take(&mut self.pomodoro_interruptions);
+        self.history.push(PomodoroRecord {
+            ended: chrono::Local::now().to_rfc3339(),
+            length: timer.time_passed().as_secs(),
+            finished,
+            task_id: self.active_task_id,
+            internal: counts.internal,
+            external: counts.external,
+        });
+    }
+
     pub fn launch_timer(&mut self) {
         match self.timer {
             None => {
-                let time = match self.state {
-                    State::Pomodoro(_) => self.timer_setting.timer,
-                    State::LongBreak => self.timer_setting.long_break,
-                    State::ShortBreak(_) => self.timer_setting.short_break,
-                };
-                self.timer = Some(Timer::new(time));
+                self.timer = Some(Timer::new(self.phase_length(&self.state)));
+                match self.state {
+                    State::Pomodoro(_) => self.run_hook(HookEvent::PomodoroStart),
+                    _ => self.run_hook(HookEvent::BreakStart),
+                }
+                self.phase_started();
             }
             // there is a timer running, we do nothing
             Some(_) => {}
@@ -509,39 +1029,35 @@ impl App {
     }
 
     pub fn update(&mut self) {
+        let now = Instant::now();
+        self.toasts.retain(|toast| toast.until > now);
+        let (_, pomodoros_per_long_break) = self.effective_settings();
         if let Some(ref mut timer) = self.timer {
             if timer.is_finished() {
-                match self.state {
-                    State::Pomodoro(0) => self.state = State::LongBreak,
-                    State::Pomodoro(x) => self.state = State::ShortBreak(x - 1),
-                    State::ShortBreak(x) => self.state = State::Pomodoro(x),
-                    State::LongBreak => {
-                        self.state = State::Pomodoro(self.state_setting.pomodoro_per_long_break)
+                if let State::Pomodoro(_) = self.state {
+                    let mut task_completed = false;
+                    if let Some(task) = self.active_task_mut() {
+                        task.completed += 1;
+                        if !task.done && task.completed >= task.pomodoros {
+                            task.done = true;
+                            task_completed = true;
+                            self.message = Some(format!("task {} done", task.title()));
+                        }
+                    }
+                    self.record_pomodoro(true);
+                    self.run_hook(HookEvent::PomodoroEnd);
+                    if task_completed {
+                        self.run_hook(HookEvent::TaskCompleted);
                     }
+                } else {
+                    self.run_hook(HookEvent::BreakEnd);
                 }
+                self.state = self.state.next(pomodoros_per_long_break);
+                self.phase_started();
 
-                // HACK more reasonable ringtone playback
-                let spec = Spec {
-                    format: Format::S16NE,
-                    channels: 2,
-                    rate: 44100,
-                };
-                assert!(spec.is_valid());
-
-                let s = Simple::new(
-                    None,                // Use the default server
-                    "Pomodoro",            // Our application’s name
-                    Direction::Playback, // We want a playback stream
-                    None,                // Use the default device
-                    "Music",             // Description of our stream
-                    &spec,               // Our sample format
-                    None,                // Use default channel map
-                    None,                // Use default buffering attributes
-                )
-                .unwrap();
-                let buf = read("./resources/achivement-bell.wav").unwrap();
-                // HACK log if fail
-                let _res = s.write(&buf);
+                if let Err(e) = play_ringtone() {
+                    log::error!("failed to play ringtone: {}", e);
+                }
 
                 // HACK notification aware of current state
                 let title = "Pomodoro";
@@ -551,8 +1067,9 @@ impl App {
                     .body(body)
                     .summary("Pomodoro Tips")
                     .finalize();
-                // HACK log if fail
-                let _res = notification.show();
+                if let Err(e) = notification.show() {
+                    log::error!("failed to show notification: {}", e);
+                }
                 // NOTE When time is up, we set timer back to None, meaning there is no timer up
                 // currently
                 self.timer = None;
@@ -562,101 +1079,1569 @@ impl App {
         } // else the timer is not started, nothing to update
     }
 
-    pub fn get_time_left(&self) -> u64 {
-        match self.timer {
-            Some(ref timer) => timer.get_time_left().as_secs(),
-            None => match self.state {
-                State::Pomodoro(_) => self.timer_setting.timer.as_secs(),
-                State::ShortBreak(_) => self.timer_setting.short_break.as_secs(),
-                State::LongBreak => self.timer_setting.long_break.as_secs(),
-            },
-        }
+    pub fn is_timer_running(&self) -> bool {
+        matches!(self.timer, Some(ref timer) if !timer.is_paused())
     }
 
-    pub fn set_timer(&mut self) {
-        match (
-            self.timer_setting_input.timer.parse::<u64>(),
-            self.timer_setting_input.short_break.parse::<u64>(),
-            self.timer_setting_input.long_break.parse::<u64>(),
-            self.timer_setting_input
-                .pomodoro_per_long_break
-                .parse::<u64>(),
-        ) {
-            (
-                Ok(mut timer),
-                Ok(mut short_break),
-                Ok(mut long_break),
-                Ok(pomodoro_per_long_break),
-            ) => {
-                timer = if timer < 100 {
-                    timer
-                } else {
-                    DEFAULT_POMODORO_LENGTH
-                };
-                short_break = if short_break < 100 {
-                    short_break
-                } else {
-                    DEFAULT_SHORT_BREAK_LENGTH
-                };
-                long_break = if long_break < 100 {
-                    long_break
-                } else {
-                    DEFAULT_LONG_BREAK_LENGTH
-                };
-                self.timer_setting = TimerSetting {
-                    timer: Duration::from_secs(timer * SECS_PER_MINUTE),
-                    short_break: Duration::from_secs(short_break * SECS_PER_MINUTE),
-                    long_break: Duration::from_secs(long_break * SECS_PER_MINUTE),
-                };
-                // HACK we can let user choose here
-                // it is a thing that when you change this value when the pomodoro loop has
-                // started; the change will apply next loop
-                // because we don't modify the value of self.state to change its behavior
-                self.state_setting = StateSetting {
-                    pomodoro_per_long_break,
-                };
-            }
-            _ => {
-                self.timer_setting_input = Input::default();
-                self.timer_setting = TimerSetting::default();
-                self.state_setting = StateSetting::default();
-            }
-        }
+    /// NOTE when the clock needs drawing again, None while it stands still
+    pub fn time_to_next_tick(&self) -> Option<Duration> {
+        self.timer.as_ref().and_then(Timer::until_next_second)
     }
 
-    pub fn add_task(&mut self) {
-        // TODO more robust add task
-        let task = Task {
-            title: self.task_manager_input.task_name.clone(),
-            notes: self.task_manager_input.task_notes.clone(),
-            pomodoros: self
-                .task_manager_input
-                .pomodoros_per_task
-                .parse()
-                .unwrap_or(1),
-        };
-        self.task_list.items.push(task);
+    pub fn is_timer_paused(&self) -> bool {
+        matches!(self.timer, Some(ref timer) if timer.is_paused())
     }
 
-    pub fn delete_task(&mut self) {
-        match self.task_list.state.selected() {
-            None => {}
-            Some(idx) => {
-                self.task_list.items.remove(idx);
-                // FIXME reasonable deletion
-                self.task_list.previous_entry();
-            }
+    pub fn next_state(&self) -> State {
+        self.state.next(self.effective_settings().1)
+    }
+
+    /// NOTE settings in use, with the active task's own ones in place of them,
+    /// along with pomodoros per long break
+    pub fn effective_settings(&self) -> (TimerSetting, u64) {
+        let mut setting = self.timer_setting.clone();
+        let mut pomodoros_per_long_break = self.state_setting.pomodoro_per_long_break;
+        let Some(timer) = self.active_task().map(|task| &task.timer) else {
+            return (setting, pomodoros_per_long_break);
         };
+        let minutes = |value: u64| Duration::from_secs(value * SECS_PER_MINUTE);
+        // NOTE a preset deleted since is skipped, per field settings still apply
+        if let Some(preset) = timer
+            .preset
+            .as_ref()
+            .and_then(|name| self.presets.iter().find(|preset| &preset.name == name))
+        {
+            setting.timer = minutes(preset.lengths.pomodoro);
+            setting.short_break = minutes(preset.lengths.short_break);
+            setting.long_break = minutes(preset.lengths.long_break);
+            pomodoros_per_long_break = preset.lengths.pomodoros_per_long_break;
+        }
+        if let Some(value) = timer.pomodoro {
+            setting.timer = minutes(value);
+        }
+        if let Some(value) = timer.short_break {
+            setting.short_break = minutes(value);
+        }
+        if let Some(value) = timer.long_break {
+            setting.long_break = minutes(value);
+        }
+        if let Some(value) = timer.pomodoros_per_long_break {
+            pomodoros_per_long_break = value;
+        }
+        (setting, pomodoros_per_long_break)
     }
 
-    pub fn quit(&mut self) {
-        match self.page_selected {
-            Page::Help => self.page_selected = Page::default(),
-            Page::Normal => self.should_quit = true,
+    fn phase_length(&self, state: &State) -> Duration {
+        let (setting, _) = self.effective_settings();
+        match state {
+            State::Pomodoro(_) => setting.timer,
+            State::ShortBreak(_) => setting.short_break,
+            State::LongBreak => setting.long_break,
         }
     }
 
-    pub fn open_help(&mut self) {
+    /// NOTE about the phase in `self.state`, before it moves on
+    fn hook_context(&self) -> HookContext {
+        HookContext {
+            phase: self.state.id(),
+            phase_length: self.phase_length(&self.state),
+            time_left: Duration::from_secs(self.get_time_left()),
+            task: self.active_task().map(|task| HookTask {
+                id: task.id,
+                title: task.title().to_string(),
+                completed: task.completed,
+                pomodoros: task.pomodoros,
+            }),
+        }
+    }
+
+    fn run_hook(&self, event: HookEvent) {
+        self.hooks.run(event, &self.hook_context());
+    }
+
+    /// NOTE shown by the D-Bus service
+    pub fn timer_status(&self) -> TimerStatus {
+        TimerStatus {
+            phase: self.state.id(),
+            running: self.is_timer_running(),
+            paused: self.is_timer_paused(),
+            remaining: self.get_time_left(),
+            active_task: self
+                .active_task()
+                .map_or(String::new(), |task| task.title().to_string()),
+        }
+    }
+
+    pub fn run_remote_command(&mut self, command: RemoteCommand) {
+        log::debug!("remote command {:?}", command);
+        match command {
+            RemoteCommand::Start if self.is_timer_paused() => self.toggle_timer(),
+            RemoteCommand::Start => self.launch_timer(),
+            RemoteCommand::Pause => self.pause_timer(),
+            RemoteCommand::Toggle => self.toggle_timer(),
+            RemoteCommand::Skip => self.abort_timer(),
+            RemoteCommand::AddTask { title, pomodoros } => {
+                let estimate = match pomodoros {
+                    0 => String::new(),
+                    pomodoros => pomodoros.to_string(),
+                };
+                match self.add_task_from_text(&title, "", &estimate) {
+                    Ok(title) => self.message = Some(format!("added task {}", title)),
+                    Err(e) => self.report_error(&e),
+                }
+            }
+        }
+    }
+
+    pub fn active_task(&self) -> Option<&Task> {
+        let id = self.active_task_id?;
+        self.task_list.items.iter().find(|task| task.id == id)
+    }
+
+    fn active_task_mut(&mut self) -> Option<&mut Task> {
+        let id = self.active_task_id?;
+        self.task_list.items.iter_mut().find(|task| task.id == id)
+    }
+
+    pub fn activate_task(&mut self) {
+        if let Some(task) = self
+            .task_list
+            .state
+            .selected()
+            .and_then(|idx| self.task_list.items.get(idx))
+        {
+            self.active_task_id = Some(task.id);
+            self.message = Some(format!("next pomodoro counts against {}", task.title()));
+        }
+    }
+
+    /// NOTE time needed to finish the pomodoros left on the active task,
+    /// breaks in between included, None if there is nothing left to do
+    pub fn time_to_finish_task(&self) -> Option<Duration> {
+        let mut left = self.active_task()?.pomodoros_left();
+        if left == 0 {
+            return None;
+        }
+        let pomodoros_per_long_break = self.effective_settings().1;
+        let mut state = self.next_state();
+        let mut total = Duration::from_secs(self.get_time_left());
+        if let State::Pomodoro(_) = self.state {
+            left -= 1;
+        }
+        while left > 0 {
+            total += self.phase_length(&state);
+            if let State::Pomodoro(_) = state {
+                left -= 1;
+            }
+            state = state.next(pomodoros_per_long_break);
+        }
+        Some(total)
+    }
+
+    pub fn get_time_left(&self) -> u64 {
+        match self.timer {
+            Some(ref timer) => timer.get_time_left().as_secs(),
+            None => self.phase_length(&self.state).as_secs(),
+        }
+    }
+
+    /// NOTE apply settings fields, rejected fields get an error beside them
+    /// and settings in use stay as they were
+    pub fn set_timer(&mut self) {
+        let input = &mut self.timer_setting_input;
+        if !input.validate() {
+            self.message = Some("error: settings not applied, fix the marked fields".to_string());
+            return;
+        }
+        let minutes = |name| Duration::from_secs(input.number(name).unwrap_or(0) * SECS_PER_MINUTE);
+        self.timer_setting = TimerSetting {
+            timer: minutes("pomodoro"),
+            short_break: minutes("short_break"),
+            long_break: minutes("long_break"),
+        };
+        // HACK we can let user choose here
+        // it is a thing that when you change this value when the pomodoro loop has
+        // started; the change will apply next loop
+        // because we don't modify the value of self.state to change its behavior
+        self.state_setting = StateSetting {
+            pomodoro_per_long_break: input.number("pomodoros_per_long_break").unwrap_or(0),
+        };
+        input.mark_saved();
+        self.message = Some("settings applied".to_string());
+    }
+
+    pub fn add_task(&mut self) {
+        let Some((title, notes, pomodoros, timer)) = self.task_from_input() else {
+            return;
+        };
+        self.push_task(&title, &notes, pomodoros).timer = timer;
+        self.message = Some(format!("added task {}", title));
+        self.task_manager_input.clear();
+    }
+
+    /// NOTE title, notes, estimate and timer settings in task manager fields,
+    /// None with errors set beside the rejected fields
+    fn task_from_input(&mut self) -> Option<(String, String, u64, TimerOverride)> {
+        let timer = self.parse_timer_override(&self.task_manager_input.text("timer"));
+        let input = &mut self.task_manager_input;
+        let valid = input.validate();
+        if let Err(ref e) = timer {
+            input.set_error("timer", e.clone());
+        }
+        let (true, Ok(timer)) = (valid, timer) else {
+            self.message = Some("error: fix the marked fields".to_string());
+            return None;
+        };
+        Some((
+            input.text("title"),
+            input.text("notes"),
+            input
+                .number("estimate")
+                .unwrap_or(DEFAULT_POMODORO_PER_TASK),
+            timer,
+        ))
+    }
+
+    /// NOTE preset named must exist
+    fn parse_timer_override(&self, text: &str) -> Result<TimerOverride, String> {
+        let timer = TimerOverride::parse(text)?;
+        if let Some(ref name) = timer.preset {
+            if !self.presets.iter().any(|preset| &preset.name == name) {
+                return Err(format!("unknown preset `{}`", name));
+            }
+        }
+        Ok(timer)
+    }
+
+    fn new_task_id(&mut self) -> u64 {
+        let id = self.next_task_id;
+        self.next_task_id += 1;
+        id
+    }
+
+    /// NOTE a task added without the task manager fields, checked by their rules: title is
+    /// trimmed and cut to fit, an empty estimate means the default. Returns the title added
+    pub fn add_task_from_text(
+        &mut self,
+        title: &str,
+        notes: &str,
+        estimate: &str,
+    ) -> Result<String, String> {
+        let title = title.trim();
+        task_field("title")
+            .validate(title)
+            .map_err(|e| format!("title {}", e))?;
+        task_field("estimate")
+            .validate(estimate)
+            .map_err(|e| format!("estimate {}", e))?;
+        let title: String = title.chars().take(TASK_TITLE_MAX_LEN).collect();
+        let notes: String = notes.chars().take(TASK_NOTES_MAX_LINE_LEN).collect();
+        let pomodoros = estimate.parse().unwrap_or(DEFAULT_POMODORO_PER_TASK);
+        self.push_task(&title, &notes, pomodoros);
+        Ok(title)
+    }
+
+    pub fn push_task(&mut self, title: &str, notes: &str, pomodoros: u64) -> &mut Task {
+        let task = Task {
+            id: self.new_task_id(),
+            title: title.to_string(),
+            notes: notes.to_string(),
+            pomodoros,
+            completed: 0,
+            done: false,
+            timer: TimerOverride::default(),
+            interruptions: Vec::new(),
+        };
+        self.task_list.items.push(task);
+        self.task_list.items.last_mut().unwrap()
+    }
+
+    /// NOTE change one timer setting by name, other fields keep their unsaved edits
+    pub fn set_timer_field(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let input = &mut self.timer_setting_input;
+        let spec = input
+            .spec(name)
+            .ok_or_else(|| format!("unknown setting `{}`", name))?;
+        spec.validate(value)
+            .map_err(|e| format!("{} {}", name, e))?;
+        let value = value.parse::<u64>().unwrap_or(0);
+        let minutes = Duration::from_secs(value * SECS_PER_MINUTE);
+        match name {
+            "pomodoro" => self.timer_setting.timer = minutes,
+            "short_break" => self.timer_setting.short_break = minutes,
+            "long_break" => self.timer_setting.long_break = minutes,
+            _ => self.state_setting.pomodoro_per_long_break = value,
+        }
+        input.set_text(name, &value.to_string());
+        input.mark_field_saved(name);
+        Ok(())
+    }
+
+    pub fn set_presets(&mut self, presets: Vec<Preset>) {
+        self.command_line.preset_names = presets.iter().map(|p| p.name.clone()).collect();
+        self.presets = presets;
+    }
+
+    fn current_lengths(&self) -> PresetLengths {
+        PresetLengths {
+            pomodoro: self.timer_setting.timer.as_secs() / SECS_PER_MINUTE,
+            short_break: self.timer_setting.short_break.as_secs() / SECS_PER_MINUTE,
+            long_break: self.timer_setting.long_break.as_secs() / SECS_PER_MINUTE,
+            pomodoros_per_long_break: self.state_setting.pomodoro_per_long_break,
+        }
+    }
+
+    /// NOTE preset the settings in use came from, None if they were set by hand
+    pub fn current_preset(&self) -> Option<usize> {
+        let lengths = self.current_lengths();
+        self.presets
+            .iter()
+            .position(|preset| preset.lengths == lengths)
+    }
+
+    /// NOTE replaces settings fields, unsaved edits included
+    pub fn apply_preset(&mut self, name: &str) -> Result<(), String> {
+        let preset = self
+            .presets
+            .iter()
+            .find(|preset| preset.name == name)
+            .ok_or_else(|| format!("unknown preset `{}`", name))?;
+        for (field, value) in preset.lengths.fields() {
+            self.timer_setting_input.set_text(field, &value.to_string());
+        }
+        if !self.timer_setting_input.validate() {
+            return Err(format!("preset {} has values out of range", name));
+        }
+        self.set_timer();
+        self.message = Some(format!("preset {} applied", name));
+        Ok(())
+    }
+
+    fn apply_preset_at(&mut self, idx: usize) {
+        let Some(name) = self.presets.get(idx).map(|preset| preset.name.clone()) else {
+            return;
+        };
+        if let Err(e) = self.apply_preset(&name) {
+            self.report_error(&e);
+        }
+    }
+
+    /// NOTE first preset when settings were set by hand
+    pub fn next_preset(&mut self) {
+        if self.presets.is_empty() {
+            return;
+        }
+        let idx = self
+            .current_preset()
+            .map_or(0, |idx| (idx + 1) % self.presets.len());
+        self.apply_preset_at(idx);
+    }
+
+    pub fn prev_preset(&mut self) {
+        if self.presets.is_empty() {
+            return;
+        }
+        let len = self.presets.len();
+        let idx = self
+            .current_preset()
+            .map_or(len - 1, |idx| (idx + len - 1) % len);
+        self.apply_preset_at(idx);
+    }
+
+    pub fn click_preset(&mut self, idx: usize) {
+        self.apply_preset_at(idx);
+    }
+
+    /// NOTE settings in use as a user preset, one with the same name is overwritten
+    pub fn save_preset(&mut self, name: &str) -> Result<PathBuf, String> {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!("invalid preset name `{}`", name));
+        }
+        let lengths = self.current_lengths();
+        match self.presets.iter_mut().find(|preset| preset.name == name) {
+            Some(preset) if preset.builtin => {
+                return Err(format!("preset `{}` is built in", name));
+            }
+            Some(preset) => preset.lengths = lengths,
+            None => {
+                self.presets.push(Preset {
+                    name: name.to_string(),
+                    builtin: false,
+                    lengths,
+                });
+                self.command_line.preset_names.push(name.to_string());
+            }
+        }
+        let path = presets::presets_path().ok_or("no home directory for presets file")?;
+        presets::save(&path, &self.presets)
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    pub fn delete_task(&mut self) {
+        let Some(idx) = self
+            .task_list
+            .state
+            .selected()
+            .filter(|&idx| idx < self.task_list.items.len())
+        else {
+            return;
+        };
+        self.remove_tasks(idx, idx);
+    }
+
+    /// NOTE remove tasks `start..=end`, selection moves to the task after them, or the new last one
+    fn remove_tasks(&mut self, start: usize, end: usize) {
+        self.snapshot("delete");
+        let removed: Vec<(usize, Task)> = self
+            .task_list
+            .items
+            .drain(start..=end)
+            .enumerate()
+            .map(|(offset, task)| (start + offset, task))
+            .collect();
+        let active_task_id = self
+            .active_task_id
+            .filter(|&id| removed.iter().any(|(_, task)| task.id == id));
+        if active_task_id.is_some() {
+            self.active_task_id = None;
+        }
+        self.checkpoint(
+            "delete",
+            Change::Removed {
+                tasks: removed,
+                active_task_id,
+            },
+        );
+        match self.task_list.items.len().checked_sub(1) {
+            Some(last) => self.task_list.state.select(Some(start.min(last))),
+            None => self.task_list.unselect(),
+        }
+    }
+
+    pub fn confirm_input(&mut self) {
+        match self.tab_selected {
+            Tabs::PomodoroSetting => self.set_timer(),
+            Tabs::TaskManager => match self.editing_task_id {
+                Some(_) => self.save_task(),
+                None => self.add_task(),
+            },
+        }
+    }
+
+    pub fn edit_task(&mut self) {
+        let Some(task) = self
+            .task_list
+            .state
+            .selected()
+            .and_then(|idx| self.task_list.items.get(idx))
+        else {
+            return;
+        };
+        let input = &mut self.task_manager_input;
+        input.clear();
+        input.set_text("title", task.title());
+        input.set_text("estimate", &task.pomodoros.to_string());
+        input.set_text("timer", &task.timer.to_string());
+        input.set_text("notes", &task.notes);
+        input.mark_saved();
+        self.editing_task_id = Some(task.id);
+        self.tab_selected = Tabs::TaskManager;
+        self.enter_insert_mode();
+    }
+
+    /// NOTE write task manager fields back to the task being edited
+    pub fn save_task(&mut self) {
+        let Some(id) = self.editing_task_id else {
+            return;
+        };
+        let Some((title, notes, pomodoros, timer)) = self.task_from_input() else {
+            return;
+        };
+        if let Some(change) = self
+            .task_list
+            .items
+            .iter()
+            .find(|task| task.id == id)
+            .map(Task::edited)
+        {
+            self.checkpoint("edit", change);
+        }
+        match self.task_list.items.iter_mut().find(|task| task.id == id) {
+            Some(task) => {
+                task.set_title(&title);
+                task.notes = notes;
+                task.pomodoros = pomodoros;
+                task.timer = timer;
+                self.message = Some(format!("saved task {}", task.title()));
+            }
+            None => self.report_error("task was deleted while editing"),
+        }
+        self.cancel_edit();
+    }
+
+    /// NOTE timer settings of the selected task, empty `spec` drops them
+    pub fn set_task_timer(&mut self, spec: &str) -> Result<String, String> {
+        let timer = self.parse_timer_override(spec)?;
+        let idx = self
+            .task_list
+            .state
+            .selected()
+            .filter(|idx| *idx < self.task_list.items.len())
+            .ok_or("no task selected")?;
+        let change = self.task_list.items[idx].edited();
+        self.checkpoint("timer", change);
+        let task = &mut self.task_list.items[idx];
+        task.timer = timer;
+        Ok(match task.timer.is_empty() {
+            true => format!("task {} uses timer settings", task.title()),
+            false => format!("task {} timer set to {}", task.title(), task.timer),
+        })
+    }
+
+    /// NOTE drop changes to the task being edited, without asking
+    pub fn cancel_edit(&mut self) {
+        if self.editing_task_id.take().is_some() {
+            self.task_manager_input.clear();
+            self.enter_normal_mode();
+        }
+    }
+
+    pub fn enter_normal_mode(&mut self) {
+        self.mode = Mode::Normal;
+        self.visual_anchor = None;
+    }
+
+    pub fn enter_insert_mode(&mut self) {
+        self.mode = Mode::Insert;
+        self.focus = Focus::Console;
+    }
+
+    pub fn enter_command_mode(&mut self) {
+        self.mode = Mode::Command;
+        self.visual_anchor = None;
+        self.command_line.clear();
+        self.message = None;
+    }
+
+    pub fn execute_command(&mut self) {
+        let line = self.command_line.input.text().to_string();
+        self.command_line.clear();
+        self.enter_normal_mode();
+        match command::execute(self, &line) {
+            Ok(message) => self.message = message,
+            Err(e) => self.report_error(&e),
+        }
+    }
+
+    pub fn complete_next(&mut self) {
+        self.command_line.complete_next();
+    }
+
+    pub fn complete_prev(&mut self) {
+        self.command_line.complete_prev();
+    }
+
+    pub fn enter_visual_mode(&mut self) {
+        if let Some(idx) = self.task_list.state.selected() {
+            self.mode = Mode::Visual;
+            self.focus = Focus::TaskList;
+            self.visual_anchor = Some(idx);
+        }
+    }
+
+    pub fn push_count_digit(&mut self, digit: usize) {
+        let count = self
+            .count
+            .unwrap_or(0)
+            .saturating_mul(10)
+            .saturating_add(digit);
+        self.count = Some(count);
+    }
+
+    fn take_count(&mut self) -> usize {
+        self.count.take().unwrap_or(1).max(1)
+    }
+
+    pub fn select_next_task(&mut self) {
+        if self.task_list.items.is_empty() {
+            return;
+        }
+        for _ in 0..self.take_count() {
+            self.task_list.next_entry();
+        }
+    }
+
+    pub fn select_prev_task(&mut self) {
+        if self.task_list.items.is_empty() {
+            return;
+        }
+        for _ in 0..self.take_count() {
+            self.task_list.previous_entry();
+        }
+    }
+
+    /// NOTE range of tasks selected in Visual mode, or `count` tasks from the selected one
+    fn selected_range(&mut self) -> Option<(usize, usize)> {
+        let idx = self.task_list.state.selected()?;
+        let last = self.task_list.items.len().checked_sub(1)?;
+        match self.visual_anchor {
+            Some(anchor) => Some((anchor.min(idx), anchor.max(idx).min(last))),
+            None => Some((idx, (idx + self.take_count() - 1).min(last))),
+        }
+    }
+
+    pub fn select_first_task(&mut self) {
+        self.task_list.select_first();
+    }
+
+    pub fn select_last_task(&mut self) {
+        self.task_list.select_last();
+    }
+
+    pub fn select_task_page_down(&mut self) {
+        let steps = self.task_list_page_size.max(1) * self.take_count();
+        self.task_list.forward(steps);
+    }
+
+    pub fn select_task_page_up(&mut self) {
+        let steps = self.task_list_page_size.max(1) * self.take_count();
+        self.task_list.backward(steps);
+    }
+
+    /// NOTE select the task at `idx`, activate it when it is selected already
+    pub fn click_task(&mut self, idx: usize) {
+        if idx >= self.task_list.items.len() {
+            return;
+        }
+        if self.mode == Mode::Insert {
+            self.enter_normal_mode();
+        }
+        self.focus = Focus::TaskList;
+        if self.task_list.state.selected() == Some(idx) && self.visual_anchor.is_none() {
+            self.activate_task();
+        } else {
+            self.task_list.state.select(Some(idx));
+        }
+    }
+
+    /// NOTE start typing into the input field at `idx`
+    pub fn click_field(&mut self, idx: usize) {
+        match self.tab_selected {
+            Tabs::TaskManager => self.task_manager_input.select_field(idx),
+            Tabs::PomodoroSetting => self.timer_setting_input.select_field(idx),
+        }
+        self.visual_anchor = None;
+        self.enter_insert_mode();
+    }
+
+    pub fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::TaskList => Focus::Console,
+            Focus::Console => Focus::TaskList,
+        };
+    }
+
+    /// NOTE moves in task list or between input fields, depending on focus
+    pub fn select_next(&mut self) {
+        match self.focus {
+            Focus::TaskList => self.select_next_task(),
+            Focus::Console => self.select_next_field(),
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        match self.focus {
+            Focus::TaskList => self.select_prev_task(),
+            Focus::Console => self.select_prev_field(),
+        }
+    }
+
+    /// NOTE activates selected task or applies input fields, depending on focus
+    pub fn confirm(&mut self) {
+        match self.focus {
+            Focus::TaskList => self.activate_task(),
+            Focus::Console => self.confirm_input(),
+        }
+    }
+
+    pub fn delete_tasks(&mut self) {
+        self.delete_task_range();
+    }
+
+    pub fn yank_tasks(&mut self) {
+        self.yank_task_range();
+    }
+
+    pub fn delete_selected_tasks(&mut self) {
+        self.delete_task_range();
+        self.enter_normal_mode();
+    }
+
+    pub fn yank_selected_tasks(&mut self) {
+        self.yank_task_range();
+        if let Some((start, _)) = self.selected_range() {
+            self.task_list.state.select(Some(start));
+        }
+        self.enter_normal_mode();
+    }
+
+    fn delete_task_range(&mut self) {
+        if let Some((start, end)) = self.selected_range() {
+            self.remove_tasks(start, end);
+        }
+    }
+
+    fn yank_task_range(&mut self) {
+        if let Some((start, end)) = self.selected_range() {
+            self.register = self.task_list.items[start..=end].to_vec();
+        }
+    }
+
+    pub fn paste_after(&mut self) {
+        let idx = match self.task_list.state.selected() {
+            Some(idx) => idx + 1,
+            None => self.task_list.items.len(),
+        };
+        self.paste_at(idx);
+    }
+
+    pub fn paste_before(&mut self) {
+        let idx = self.task_list.state.selected().unwrap_or(0);
+        self.paste_at(idx);
+    }
+
+    fn paste_at(&mut self, idx: usize) {
+        if self.register.is_empty() {
+            return;
+        }
+        let count = self.take_count();
+        let copies: Vec<Task> = (0..count)
+            .flat_map(|_| self.register.iter().cloned())
+            .collect();
+        let pasted: Vec<Task> = copies
+            .into_iter()
+            .map(|task| Task {
+                id: self.new_task_id(),
+                completed: 0,
+                done: false,
+                interruptions: Vec::new(),
+                ..task
+            })
+            .collect();
+        let tasks = pasted.iter().map(Task::progress).collect();
+        self.checkpoint(
+            "paste",
+            Change::Added {
+                tasks,
+                records: Vec::new(),
+            },
+        );
+        self.task_list.items.splice(idx..idx, pasted);
+        self.task_list.state.select(Some(idx));
+    }
+
+    pub fn toggle_task_done(&mut self) {
+        let Some(idx) = self
+            .task_list
+            .state
+            .selected()
+            .filter(|&idx| idx < self.task_list.items.len())
+        else {
+            return;
+        };
+        let task = &self.task_list.items[idx];
+        let change = Change::Done {
+            id: task.id,
+            done: task.done,
+        };
+        self.checkpoint("done", change);
+        let task = &mut self.task_list.items[idx];
+        task.done = !task.done;
+    }
+
+    /// NOTE move done tasks out of task list, selection stays on the same task if kept
+    pub fn archive_done_tasks(&mut self) {
+        let selected_id = self
+            .task_list
+            .state
+            .selected()
+            .and_then(|idx| self.task_list.items.get(idx))
+            .map(|task| task.id);
+        if !self.task_list.items.iter().any(|task| task.done) {
+            self.message = Some("no done tasks to archive".to_string());
+            return;
+        }
+        self.snapshot("archive");
+        let archived = self
+            .task_list
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.done)
+            .map(|(idx, task)| (idx, task.id))
+            .collect();
+        self.checkpoint(
+            "archive",
+            Change::Archived {
+                tasks: archived,
+                active_task_id: self.active_task_id,
+            },
+        );
+        let (done, todo): (Vec<Task>, Vec<Task>) =
+            self.task_list.items.drain(..).partition(|task| task.done);
+        self.task_list.items = todo;
+        if done.iter().any(|task| Some(task.id) == self.active_task_id) {
+            self.active_task_id = None;
+        }
+        self.message = Some(format!("archived {} tasks", done.len()));
+        self.archived_tasks.extend(done);
+
+        let idx = self
+            .task_list
+            .items
+            .iter()
+            .position(|task| Some(task.id) == selected_id);
+        match (idx, self.task_list.items.len()) {
+            (_, 0) => self.task_list.unselect(),
+            (Some(idx), _) => self.task_list.state.select(Some(idx)),
+            (None, len) => {
+                let idx = self.task_list.state.selected().unwrap_or(0).min(len - 1);
+                self.task_list.state.select(Some(idx));
+            }
+        }
+    }
+
+    pub fn move_task_down(&mut self) {
+        let steps = self.take_count();
+        if let Some(idx) = self.task_list.state.selected() {
+            let last = self.task_list.items.len().saturating_sub(1);
+            self.move_task(idx, (idx + steps).min(last));
+        }
+    }
+
+    pub fn move_task_up(&mut self) {
+        let steps = self.take_count();
+        if let Some(idx) = self.task_list.state.selected() {
+            self.move_task(idx, idx.saturating_sub(steps));
+        }
+    }
+
+    fn move_task(&mut self, from: usize, to: usize) {
+        if from == to || from >= self.task_list.items.len() {
+            return;
+        }
+        let id = self.task_list.items[from].id;
+        self.checkpoint("move", Change::Moved { id, from });
+        let task = self.task_list.items.remove(from);
+        self.task_list.items.insert(to, task);
+        self.task_list.state.select(Some(to));
+    }
+
+    /// NOTE undo is gone once the app quits, a snapshot on disk is not.
+    /// Failing to write one is logged, the change still goes ahead
+    fn snapshot(&self, reason: &str) {
+        match backup::snapshot(self.saved_data(), reason) {
+            Ok(path) => log::debug!("snapshot before {} at {}", reason, path.display()),
+            Err(e) => log::warn!("no snapshot before {}: {}", reason, e),
+        }
+    }
+
+    /// NOTE tasks added by `change` with other progress than they were added with
+    fn has_progress_since(&self, change: &Change) -> bool {
+        let added = match change {
+            Change::Added { tasks, .. } => tasks,
+            Change::Replaced { imported, .. } => imported,
+            _ => return false,
+        };
+        added.iter().any(|before| {
+            self.task_list
+                .items
+                .iter()
+                .chain(self.archived_tasks.iter())
+                .find(|task| task.id == before.id)
+                .is_some_and(|task| task.progress() != *before)
+        })
+    }
+
+    /// NOTE `name` is shown when the change is undone
+    fn checkpoint(&mut self, name: &'static str, change: Change) {
+        if self.undo_stack.len() == UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(UndoEntry {
+            name,
+            selected: self.task_list.state.selected(),
+            change,
+        });
+    }
+
+    /// NOTE take back the last delete, edit, move, paste, archive or import,
+    /// tasks changed since keep their progress. A paste or import is dropped instead once
+    /// a task it added got pomodoros or interruptions, undoing it would lose them
+    pub fn undo(&mut self) {
+        let Some(entry) = self.undo_stack.pop() else {
+            self.message = Some("already at oldest change".to_string());
+            return;
+        };
+        if self.has_progress_since(&entry.change) {
+            self.message = Some(format!(
+                "can not undo {}, its tasks got progress since",
+                entry.name
+            ));
+            return;
+        }
+        let items = &mut self.task_list.items;
+        let position = |items: &[Task], id: u64| items.iter().position(|task| task.id == id);
+        match entry.change {
+            Change::Removed {
+                tasks,
+                active_task_id,
+            } => {
+                if active_task_id.is_some() {
+                    self.active_task_id = active_task_id;
+                }
+                for (idx, task) in tasks {
+                    items.insert(idx.min(items.len()), task);
+                }
+            }
+            Change::Added { tasks, records } => {
+                self.history
+                    .retain(|record| !records.contains(&record.ended));
+                let ids: Vec<u64> = tasks.iter().map(|task| task.id).collect();
+                items.retain(|task| !ids.contains(&task.id));
+                self.archived_tasks.retain(|task| !ids.contains(&task.id));
+                if self.active_task_id.is_some_and(|id| ids.contains(&id)) {
+                    self.active_task_id = None;
+                }
+            }
+            Change::Edited {
+                id,
+                title,
+                notes,
+                pomodoros,
+                timer,
+            } => {
+                if let Some(task) = items.iter_mut().find(|task| task.id == id) {
+                    task.title = title;
+                    task.notes = notes;
+                    task.pomodoros = pomodoros;
+                    task.timer = timer;
+                }
+            }
+            Change::Done { id, done } => {
+                if let Some(task) = items.iter_mut().find(|task| task.id == id) {
+                    task.done = done;
+                }
+            }
+            Change::Moved { id, from } => {
+                if let Some(idx) = position(items, id) {
+                    let task = items.remove(idx);
+                    items.insert(from.min(items.len()), task);
+                }
+            }
+            Change::Archived {
+                tasks,
+                active_task_id,
+            } => {
+                for (idx, id) in tasks {
+                    if let Some(archived) = position(&self.archived_tasks, id) {
+                        let task = self.archived_tasks.remove(archived);
+                        items.insert(idx.min(items.len()), task);
+                    }
+                }
+                if active_task_id.is_some_and(|id| position(items, id).is_some()) {
+                    self.active_task_id = active_task_id;
+                }
+            }
+            Change::Replaced {
+                tasks,
+                archived_tasks,
+                active_task_id,
+                next_task_id,
+                history,
+                imported_history,
+                ..
+            } => {
+                let since = self
+                    .history
+                    .split_off(imported_history.min(self.history.len()));
+                self.history = history;
+                self.history.extend(since);
+                *items = tasks;
+                self.archived_tasks = archived_tasks;
+                self.active_task_id = active_task_id;
+                self.next_task_id = self.next_task_id.max(next_task_id);
+            }
+        }
+        // NOTE ids handed out since must never come back, whatever undo put back
+        let max_id = self
+            .task_list
+            .items
+            .iter()
+            .chain(self.archived_tasks.iter())
+            .map(|task| task.id)
+            .max();
+        if let Some(max_id) = max_id {
+            self.next_task_id = self.next_task_id.max(max_id + 1);
+        }
+        match self.task_list.items.len().checked_sub(1) {
+            Some(last) => self
+                .task_list
+                .state
+                .select(Some(entry.selected.unwrap_or(0).min(last))),
+            None => self.task_list.unselect(),
+        }
+        self.message = Some(format!("undid {}", entry.name));
+    }
+
+    /// NOTE pomodoros finished and tasks done, archived tasks included
+    pub fn statistics(&self) -> (u64, usize) {
+        let tasks = self
+            .task_list
+            .items
+            .iter()
+            .chain(self.archived_tasks.iter());
+        tasks.fold((0, 0), |(pomodoros, done), task| {
+            (pomodoros + task.completed, done + task.done as usize)
+        })
+    }
+
+    /// NOTE interruptions logged on tasks, archived tasks included
+    pub fn interruption_totals(&self) -> InterruptionCounts {
+        let tasks = self
+            .task_list
+            .items
+            .iter()
+            .chain(self.archived_tasks.iter());
+        tasks.fold(InterruptionCounts::default(), |total, task| {
+            let counts = task.interruption_counts();
+            InterruptionCounts {
+                internal: total.internal + counts.internal,
+                external: total.external + counts.external,
+            }
+        })
+    }
+
+    /// NOTE a pomodoro timer is running or paused
+    fn in_pomodoro(&self) -> bool {
+        matches!(self.state, State::Pomodoro(_)) && self.timer.is_some()
+    }
+
+    /// NOTE counted against the session, and the active task if there is one
+    pub fn log_interruption(
+        &mut self,
+        kind: InterruptionKind,
+        note: &str,
+    ) -> Result<String, String> {
+        if !self.in_pomodoro() {
+            return Err("interruptions are logged during a pomodoro".to_string());
+        }
+        self.session_interruptions.add(kind);
+        self.pomodoro_interruptions.add(kind);
+        log::info!("{} interruption: {}", kind.name(), note);
+        let interruption = Interruption {
+            kind,
+            time: chrono::Local::now().to_rfc3339(),
+            note: note.trim().to_string(),
+        };
+        Ok(match self.active_task_mut() {
+            Some(task) => {
+                task.interruptions.push(interruption);
+                format!("{} interruption logged on {}", kind.name(), task.title())
+            }
+            None => format!("{} interruption logged", kind.name()),
+        })
+    }
+
+    /// NOTE opens command line to take a note, enter logs it with or without one
+    fn start_interruption(&mut self, kind: InterruptionKind) {
+        if !self.in_pomodoro() {
+            self.message = Some("error: interruptions are logged during a pomodoro".to_string());
+            return;
+        }
+        self.enter_command_mode();
+        self.command_line
+            .edit(|line| line.insert_str(&format!("interruption {} ", kind.name())));
+    }
+
+    pub fn log_internal_interruption(&mut self) {
+        self.start_interruption(InterruptionKind::Internal);
+    }
+
+    pub fn log_external_interruption(&mut self) {
+        self.start_interruption(InterruptionKind::External);
+    }
+
+    pub fn suspend(&mut self) {
+        self.suspend_requested = true;
+    }
+
+    pub fn saved_data(&self) -> SavedData {
+        SavedData {
+            next_task_id: self.next_task_id,
+            active_task_id: self.active_task_id,
+            settings: SavedSettings {
+                pomodoro: self.timer_setting.timer.as_secs(),
+                short_break: self.timer_setting.short_break.as_secs(),
+                long_break: self.timer_setting.long_break.as_secs(),
+                pomodoros_per_long_break: self.state_setting.pomodoro_per_long_break,
+            },
+            tasks: self.task_list.items.clone(),
+            archived_tasks: self.archived_tasks.clone(),
+            history: self.history.clone(),
+        }
+    }
+
+    /// NOTE replace tasks and settings with saved ones, zero settings keep their defaults
+    pub fn load_saved_data(&mut self, data: SavedData) {
+        let unsaved = SavedSettings::default();
+        let settings = match data.settings.check() {
+            Ok(()) => &data.settings,
+            Err(e) => {
+                log::warn!("saved settings ignored, {}", e);
+                &unsaved
+            }
+        };
+        let lengths = [
+            ("pomodoro", settings.pomodoro, &mut self.timer_setting.timer),
+            (
+                "short_break",
+                settings.short_break,
+                &mut self.timer_setting.short_break,
+            ),
+            (
+                "long_break",
+                settings.long_break,
+                &mut self.timer_setting.long_break,
+            ),
+        ];
+        for (name, secs, length) in lengths {
+            if secs > 0 {
+                *length = Duration::from_secs(secs);
+                let minutes = (secs / SECS_PER_MINUTE).to_string();
+                self.timer_setting_input.set_text(name, &minutes);
+                self.timer_setting_input.mark_field_saved(name);
+            }
+        }
+        if settings.pomodoros_per_long_break > 0 {
+            let count = settings.pomodoros_per_long_break;
+            self.state_setting.pomodoro_per_long_break = count;
+            let name = "pomodoros_per_long_break";
+            self.timer_setting_input.set_text(name, &count.to_string());
+            self.timer_setting_input.mark_field_saved(name);
+        }
+
+        // NOTE ids must not be given out again, even if the file says otherwise
+        let max_id = data
+            .tasks
+            .iter()
+            .chain(data.archived_tasks.iter())
+            .map(|task| task.id)
+            .max()
+            .unwrap_or(0);
+        self.next_task_id = data.next_task_id.max(max_id + 1);
+        self.task_list.items = data.tasks;
+        self.archived_tasks = data.archived_tasks;
+        self.history = data.history;
+        self.active_task_id = data
+            .active_task_id
+            .filter(|id| self.task_list.items.iter().any(|task| task.id == *id));
+        self.task_list
+            .state
+            .select(if self.task_list.items.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+    }
+
+    /// NOTE tasks and settings from a bundle, task list changes can be undone.
+    /// Returns how many tasks were added
+    pub fn import_data(&mut self, data: SavedData, mode: ImportMode) -> usize {
+        match mode {
+            ImportMode::Replace => {
+                let change = Change::Replaced {
+                    tasks: self.task_list.items.clone(),
+                    archived_tasks: self.archived_tasks.clone(),
+                    active_task_id: self.active_task_id,
+                    next_task_id: self.next_task_id,
+                    imported: data
+                        .tasks
+                        .iter()
+                        .chain(data.archived_tasks.iter())
+                        .map(Task::progress)
+                        .collect(),
+                    history: self.history.clone(),
+                    imported_history: data.history.len(),
+                };
+                self.checkpoint("import", change);
+                let count = data.tasks.len() + data.archived_tasks.len();
+                self.load_saved_data(data);
+                count
+            }
+            // NOTE tasks get new ids, ones already here are skipped so importing twice adds nothing.
+            // Same for history records, which follow their tasks to the ids given here
+            ImportMode::Merge => {
+                let mut added = Vec::new();
+                let mut ids = HashMap::new();
+                for (task, archived) in data
+                    .tasks
+                    .into_iter()
+                    .map(|task| (task, false))
+                    .chain(data.archived_tasks.into_iter().map(|task| (task, true)))
+                {
+                    let list = match archived {
+                        true => &self.archived_tasks,
+                        false => &self.task_list.items,
+                    };
+                    if let Some(same) = list.iter().find(|t| t.same_as(&task)) {
+                        ids.insert(task.id, same.id);
+                        continue;
+                    }
+                    let id = self.new_task_id();
+                    ids.insert(task.id, id);
+                    let task = Task { id, ..task };
+                    added.push(task.progress());
+                    match archived {
+                        true => self.archived_tasks.push(task),
+                        false => self.task_list.items.push(task),
+                    }
+                }
+                let records: Vec<PomodoroRecord> = data
+                    .history
+                    .into_iter()
+                    .filter(|record| !self.history.iter().any(|r| r.ended == record.ended))
+                    .map(|record| PomodoroRecord {
+                        task_id: record.task_id.and_then(|id| ids.get(&id).copied()),
+                        ..record
+                    })
+                    .collect();
+                let count = added.len();
+                if count > 0 || !records.is_empty() {
+                    let ended = records.iter().map(|record| record.ended.clone()).collect();
+                    self.history.extend(records);
+                    self.history.sort_by(|a, b| a.ended.cmp(&b.ended));
+                    self.checkpoint(
+                        "import",
+                        Change::Added {
+                            tasks: added,
+                            records: ended,
+                        },
+                    );
+                }
+                if self.task_list.state.selected().is_none() && !self.task_list.items.is_empty() {
+                    self.task_list.state.select(Some(0));
+                }
+                count
+            }
+        }
+    }
+
+    pub fn quit(&mut self) {
+        match self.page_selected {
+            Page::Help | Page::Log | Page::Stats => self.page_selected = Page::default(),
+            Page::Normal => self.should_quit = true,
+        }
+    }
+
+    pub fn open_help(&mut self) {
         self.page_selected = Page::Help;
+        self.help_scroll = 0;
+    }
+
+    /// NOTE opens at the newest entries
+    pub fn open_log(&mut self) {
+        self.page_selected = Page::Log;
+        self.help_scroll = u16::MAX;
+    }
+
+    pub fn open_stats(&mut self) {
+        self.page_selected = Page::Stats;
+        self.help_scroll = 0;
+    }
+
+    /// NOTE stats page lines, tasks named from task list and archive
+    pub fn stats_report(&self) -> Vec<String> {
+        let title = |id| {
+            self.task_list
+                .items
+                .iter()
+                .chain(self.archived_tasks.iter())
+                .find(|task| task.id == id)
+                .map(Task::title)
+        };
+        history::report(&self.history, chrono::Local::now().date_naive(), title)
+    }
+
+    /// NOTE failures are logged besides the message line, input mistakes only go to the latter
+    fn report_error(&mut self, error: &str) {
+        log::error!("{}", error);
+        self.message = Some(format!("error: {}", error));
+    }
+
+    /// NOTE errors pop up for a while, besides being in log page
+    pub fn log_entry_added(&mut self, entry: &LogEntry) {
+        if entry.level == Level::Error {
+            if self.toasts.len() == TOAST_LIMIT {
+                self.toasts.remove(0);
+            }
+            self.toasts.push(Toast {
+                text: entry.message.clone(),
+                until: Instant::now() + TOAST_DURATION,
+            });
+        }
+    }
+
+    /// NOTE when the oldest toast goes away
+    pub fn time_to_toast_expiry(&self) -> Option<Duration> {
+        self.toasts
+            .iter()
+            .map(|toast| toast.until.saturating_duration_since(Instant::now()))
+            .min()
+    }
+
+    pub fn scroll_help_down(&mut self) {
+        self.help_scroll = self.help_scroll.saturating_add(1);
+    }
+
+    pub fn scroll_help_up(&mut self) {
+        self.help_scroll = self.help_scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_help_page_down(&mut self) {
+        self.help_scroll = self.help_scroll.saturating_add(self.help_page_size);
+    }
+
+    pub fn scroll_help_page_up(&mut self) {
+        self.help_scroll = self.help_scroll.saturating_sub(self.help_page_size);
+    }
+
+    pub fn scroll_help_top(&mut self) {
+        self.help_scroll = 0;
+    }
+
+    pub fn scroll_help_bottom(&mut self) {
+        self.help_scroll = u16::MAX;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Once;
+
+    /// NOTE deletes and archives write snapshots, keep them out of the real data dir.
+    /// Debug builds start with sample tasks, these start empty
+    fn app(titles: &[&str]) -> App {
+        static TEST_DATA_DIR: Once = Once::new();
+        TEST_DATA_DIR.call_once(|| {
+            let dir = std::env::temp_dir().join(format!("pomodoro-test-{}", std::process::id()));
+            std::env::set_var("XDG_DATA_HOME", dir);
+        });
+        let mut app = App::default();
+        app.task_list.items.clear();
+        for title in titles {
+            app.push_task(title, "", 1);
+        }
+        app.task_list.state.select(Some(0));
+        app
+    }
+
+    fn titles(app: &App) -> Vec<&str> {
+        app.task_list.items.iter().map(Task::title).collect()
+    }
+
+    fn record(ended: &str, task_id: Option<u64>) -> PomodoroRecord {
+        PomodoroRecord {
+            ended: ended.to_string(),
+            length: 1500,
+            finished: true,
+            task_id,
+            internal: 0,
+            external: 0,
+        }
+    }
+
+    #[test]
+    fn undo_delete_puts_tasks_back_in_place() {
+        let mut app = app(&["a", "b", "c"]);
+        app.active_task_id = Some(app.task_list.items[1].id);
+        app.task_list.state.select(Some(1));
+        app.delete_tasks();
+        assert_eq!(titles(&app), ["a", "c"]);
+        assert_eq!(app.active_task_id, None);
+        app.undo();
+        assert_eq!(titles(&app), ["a", "b", "c"]);
+        assert_eq!(app.active_task_id, Some(app.task_list.items[1].id));
+    }
+
+    #[test]
+    fn undo_takes_changes_back_newest_first() {
+        let mut app = app(&["a", "b"]);
+        app.move_task_down();
+        app.toggle_task_done();
+        assert_eq!(titles(&app), ["b", "a"]);
+        app.undo();
+        assert!(!app.task_list.items[1].done);
+        app.undo();
+        assert_eq!(titles(&app), ["a", "b"]);
+        app.undo();
+        assert_eq!(app.message.as_deref(), Some("already at oldest change"));
+    }
+
+    #[test]
+    fn undo_archive_keeps_task_progress() {
+        let mut app = app(&["a", "b"]);
+        app.toggle_task_done();
+        app.archive_done_tasks();
+        assert_eq!(titles(&app), ["b"]);
+        app.archived_tasks[0].completed = 2;
+        app.undo();
+        assert_eq!(titles(&app), ["a", "b"]);
+        assert_eq!(app.task_list.items[0].completed, 2);
+        assert!(app.archived_tasks.is_empty());
+    }
+
+    #[test]
+    fn undo_stack_forgets_past_its_limit() {
+        let mut app = app(&["a"]);
+        for _ in 0..UNDO_LIMIT + 1 {
+            app.toggle_task_done();
+        }
+        assert_eq!(app.undo_stack.len(), UNDO_LIMIT);
+        for _ in 0..UNDO_LIMIT {
+            app.undo();
+        }
+        // NOTE the first toggle is forgotten
+        assert!(app.task_list.items[0].done);
+    }
+
+    #[test]
+    fn undo_paste_is_refused_once_its_tasks_got_progress() {
+        let mut app = app(&["a"]);
+        app.yank_tasks();
+        app.paste_after();
+        assert_eq!(titles(&app), ["a", "a"]);
+        app.task_list.items[1].completed = 1;
+        app.undo();
+        assert_eq!(titles(&app), ["a", "a"]);
+        assert_eq!(
+            app.message.as_deref(),
+            Some("can not undo paste, its tasks got progress since")
+        );
+        assert!(app.undo_stack.is_empty());
+    }
+
+    #[test]
+    fn set_timer_keeps_settings_while_a_field_is_wrong() {
+        let mut app = app(&[]);
+        let before = app.timer_setting.timer;
+        app.timer_setting_input.set_text("pomodoro", "30");
+        app.timer_setting_input.set_text("long_break", "0");
+        assert!(app.timer_setting_input.is_modified());
+        app.set_timer();
+        assert_eq!(app.timer_setting.timer, before);
+        let field = |name| {
+            app.timer_setting_input
+                .fields()
+                .iter()
+                .find(|field| field.spec.name == name)
+                .unwrap()
+        };
+        assert!(field("long_break").error.is_some());
+        assert!(field("pomodoro").error.is_none());
+        assert!(field("pomodoro").is_modified());
+    }
+
+    #[test]
+    fn set_timer_applies_and_saves_the_fields() {
+        let mut app = app(&[]);
+        app.timer_setting_input.set_text("pomodoro", "30");
+        app.timer_setting_input
+            .set_text("pomodoros_per_long_break", "3");
+        app.set_timer();
+        assert_eq!(app.timer_setting.timer, Duration::from_secs(30 * 60));
+        assert_eq!(app.state_setting.pomodoro_per_long_break, 3);
+        assert!(!app.timer_setting_input.is_modified());
+    }
+
+    #[test]
+    fn set_timer_field_checks_the_field_rules() {
+        let mut app = app(&[]);
+        app.timer_setting_input.set_text("long_break", "20");
+        assert_eq!(app.set_timer_field("short_break", "7"), Ok(()));
+        assert_eq!(app.timer_setting.short_break, Duration::from_secs(7 * 60));
+        // NOTE other fields keep their unsaved edits
+        assert!(app.timer_setting_input.is_modified());
+        assert_eq!(
+            app.set_timer_field("short_break", "0"),
+            Err("short_break must be between 1 and 99 min".to_string())
+        );
+        assert_eq!(
+            app.set_timer_field("lunch", "60"),
+            Err("unknown setting `lunch`".to_string())
+        );
+        assert_eq!(app.timer_setting.short_break, Duration::from_secs(7 * 60));
+    }
+
+    #[test]
+    fn undo_merge_import_drops_its_tasks_and_history() {
+        let bundled = app(&["b"]).task_list.items;
+        let mut app = app(&["a"]);
+        app.history.push(record("2024-01-01T10:00:00+00:00", None));
+        let bundled_id = bundled[0].id;
+        let data = SavedData {
+            tasks: bundled,
+            history: vec![
+                record("2024-01-01T10:00:00+00:00", None),
+                record("2024-01-02T10:00:00+00:00", Some(bundled_id)),
+            ],
+            ..SavedData::default()
+        };
+        assert_eq!(app.import_data(data, ImportMode::Merge), 1);
+        let imported_id = app.task_list.items[1].id;
+        assert_eq!(app.history.len(), 2);
+        assert_eq!(app.history[1].task_id, Some(imported_id));
+        app.undo();
+        assert_eq!(titles(&app), ["a"]);
+        assert_eq!(app.history.len(), 1);
+    }
+
+    #[test]
+    fn undo_replace_import_keeps_history_made_since() {
+        let mut app = app(&["a"]);
+        app.history.push(record("2024-01-01T10:00:00+00:00", None));
+        let data = SavedData {
+            history: vec![record("2024-01-02T10:00:00+00:00", None)],
+            ..SavedData::default()
+        };
+        app.import_data(data, ImportMode::Replace);
+        assert!(app.task_list.items.is_empty());
+        app.history.push(record("2024-01-03T10:00:00+00:00", None));
+        app.undo();
+        assert_eq!(titles(&app), ["a"]);
+        let ended: Vec<&str> = app.history.iter().map(|r| r.ended.as_str()).collect();
+        assert_eq!(
+            ended,
+            ["2024-01-01T10:00:00+00:00", "2024-01-03T10:00:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn load_saved_data_drops_settings_out_of_range() {
+        let mut app = app(&[]);
+        let mut data = SavedData::default();
+        data.settings.pomodoro = 30 * 60;
+        data.settings.pomodoros_per_long_break = 1000;
+        data.history.push(record("2024-01-01T10:00:00+00:00", None));
+        app.load_saved_data(data);
+        assert_eq!(app.timer_setting.timer, App::default().timer_setting.timer);
+        assert_eq!(app.history.len(), 1);
+
+        let mut data = SavedData::default();
+        data.settings.pomodoro = 30 * 60;
+        app.load_saved_data(data);
+        assert_eq!(app.timer_setting.timer, Duration::from_secs(30 * 60));
     }
 }
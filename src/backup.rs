@@ -0,0 +1,310 @@
+use crate::config;
+use crate::hooks;
+use crate::mpris;
+use crate::presets;
+use crate::storage::{self, SavedData};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// NOTE bumped whenever a bundle written by this version can not be read by older ones
+pub const BUNDLE_VERSION: u32 = 1;
+// NOTE oldest snapshots are removed past this
+const SNAPSHOT_LIMIT: usize = 10;
+
+/// NOTE everything kept between runs in one file, made by `:export`, taken by `:import`.
+/// Config files are carried as their text, None for a file there was none of
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    pub created: String,
+    pub keybindings: Option<String>,
+    pub presets: Option<String>,
+    pub hooks: Option<String>,
+    pub mpris: Option<String>,
+    // NOTE tasks, settings and pomodoro history
+    pub data: SavedData,
+}
+
+impl Bundle {
+    /// NOTE config files as (name, text in bundle, path here)
+    fn config_files(&self) -> [(&'static str, Option<&str>, Option<PathBuf>); 4] {
+        [
+            (
+                "keybindings",
+                self.keybindings.as_deref(),
+                config::keybindings_path(),
+            ),
+            ("presets", self.presets.as_deref(), presets::presets_path()),
+            ("hooks", self.hooks.as_deref(), hooks::hooks_path()),
+            ("mpris", self.mpris.as_deref(), mpris::mpris_path()),
+        ]
+    }
+}
+
+/// NOTE read before the rest of a bundle, so a newer one is told apart from a broken one
+#[derive(Deserialize)]
+struct BundleVersion {
+    version: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    // NOTE tasks and history not there yet are added, and config files missing here,
+    // settings in use are kept
+    Merge,
+    // NOTE tasks, history, settings and config files are swapped for the bundled ones
+    Replace,
+}
+
+impl ImportMode {
+    pub const NAMES: [&'static str; 2] = ["merge", "replace"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "merge" => Some(ImportMode::Merge),
+            "replace" => Some(ImportMode::Replace),
+            _ => None,
+        }
+    }
+}
+
+/// NOTE `~/` at the start means home directory, like in a shell
+pub fn expand_path(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// NOTE None when there is no such file
+fn read_config(path: Option<PathBuf>) -> Result<Option<String>, String> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("failed to read {}: {}", path.display(), e)),
+    }
+}
+
+fn bundle(data: SavedData) -> Result<Bundle, String> {
+    Ok(Bundle {
+        version: BUNDLE_VERSION,
+        created: chrono::Local::now().to_rfc3339(),
+        keybindings: read_config(config::keybindings_path())?,
+        presets: read_config(presets::presets_path())?,
+        hooks: read_config(hooks::hooks_path())?,
+        mpris: read_config(mpris::mpris_path())?,
+        data,
+    })
+}
+
+fn write_bundle(path: &Path, bundle: &Bundle) -> Result<(), String> {
+    let text = toml::to_string(bundle).map_err(|e| e.to_string())?;
+    storage::write_file(path, &text)
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+pub fn export(path: &Path, data: SavedData) -> Result<(), String> {
+    write_bundle(path, &bundle(data)?)
+}
+
+pub fn read(path: &Path) -> Result<Bundle, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let invalid = |e: toml::de::Error| format!("invalid bundle {}: {}", path.display(), e);
+    match toml::from_str::<BundleVersion>(&text)
+        .map_err(invalid)?
+        .version
+    {
+        Some(BUNDLE_VERSION) => {}
+        Some(version) if version > BUNDLE_VERSION => {
+            return Err(format!(
+                "{} is version {}, made by a newer pomodoro, this one reads version {}",
+                path.display(),
+                version,
+                BUNDLE_VERSION
+            ))
+        }
+        Some(version) => return Err(format!("unknown bundle version {}", version)),
+        None => return Err(format!("{} is not a pomodoro bundle", path.display())),
+    }
+    let bundle: Bundle = toml::from_str(&text).map_err(invalid)?;
+    bundle
+        .data
+        .settings
+        .check()
+        .map_err(|e| format!("invalid bundle {}: {}", path.display(), e))?;
+    Ok(bundle)
+}
+
+/// NOTE `$XDG_DATA_HOME/pomodoro/snapshots`
+fn snapshots_dir() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("snapshots"))
+}
+
+/// NOTE bundle of what is about to be overwritten, `reason` goes into the file name.
+/// Only the newest `SNAPSHOT_LIMIT` snapshots are kept
+pub fn snapshot(data: SavedData, reason: &str) -> Result<PathBuf, String> {
+    let dir = snapshots_dir().ok_or("no home directory for snapshots")?;
+    let name = format!(
+        "snapshot-{}-{}.toml",
+        chrono::Local::now().format("%Y%m%d-%H%M%S%.3f"),
+        reason
+    );
+    let path = dir.join(name);
+    write_bundle(&path, &bundle(data)?)?;
+
+    let mut snapshots: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(|e| format!("failed to list {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("snapshot-") && name.ends_with(".toml"))
+        })
+        .collect();
+    // NOTE names start with the time they were taken, so oldest sort first
+    snapshots.sort();
+    let excess = snapshots.len().saturating_sub(SNAPSHOT_LIMIT);
+    for old in snapshots.iter().take(excess) {
+        if let Err(e) = fs::remove_file(old) {
+            log::warn!("failed to remove old snapshot {}: {}", old.display(), e);
+        }
+    }
+    Ok(path)
+}
+
+/// NOTE bundled config files are written when replacing, or when merging and there is none yet.
+/// Returns the names of the files written, they are read at startup only
+pub fn import_config(bundle: &Bundle, mode: ImportMode) -> Result<Vec<&'static str>, String> {
+    let mut written = Vec::new();
+    for (name, text, path) in bundle.config_files() {
+        let (Some(text), Some(path)) = (text, path) else {
+            continue;
+        };
+        if mode == ImportMode::Merge && path.exists() {
+            continue;
+        }
+        storage::write_file(&path, text)
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+        written.push(name);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::PomodoroRecord;
+
+    /// NOTE bundle file of its own for each test, removed when done
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, text: &str) -> Self {
+            let path = env::temp_dir().join(format!(
+                "pomodoro-bundle-{}-{}.toml",
+                std::process::id(),
+                name
+            ));
+            fs::write(&path, text).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn read_text(name: &str, text: &str) -> Result<Bundle, String> {
+        let file = TempFile::new(name, text);
+        read(&file.0).map_err(|e| e.replace(&file.0.display().to_string(), "FILE"))
+    }
+
+    #[test]
+    fn read_takes_back_what_was_written() {
+        let file = TempFile::new("roundtrip", "");
+        let mut data = SavedData {
+            next_task_id: 3,
+            ..SavedData::default()
+        };
+        data.settings.pomodoro = 25 * 60;
+        data.history.push(PomodoroRecord {
+            ended: "2024-01-10T10:00:00+01:00".to_string(),
+            length: 1500,
+            finished: true,
+            task_id: Some(2),
+            internal: 1,
+            external: 0,
+        });
+        let bundle = Bundle {
+            version: BUNDLE_VERSION,
+            hooks: Some("[pomodoro_end]\ncommand = \"true\"\n".to_string()),
+            data,
+            ..Bundle::default()
+        };
+        write_bundle(&file.0, &bundle).unwrap();
+        let read = read(&file.0).unwrap();
+        assert_eq!(read.data.next_task_id, 3);
+        assert_eq!(read.data.history, bundle.data.history);
+        assert_eq!(read.hooks, bundle.hooks);
+        assert_eq!(read.keybindings, None);
+    }
+
+    #[test]
+    fn read_takes_bundles_without_config_files_and_history() {
+        let bundle = read_text(
+            "old",
+            "version = 1\ncreated = \"\"\nkeybindings = \"\"\n\n[data]\nnext_task_id = 1\n",
+        )
+        .unwrap();
+        assert_eq!(bundle.keybindings.as_deref(), Some(""));
+        assert_eq!((bundle.presets, bundle.mpris), (None, None));
+        assert!(bundle.data.history.is_empty());
+    }
+
+    #[test]
+    fn read_tells_versions_apart() {
+        assert_eq!(
+            read_text("newer", "version = 2\n").unwrap_err(),
+            "FILE is version 2, made by a newer pomodoro, this one reads version 1"
+        );
+        assert_eq!(
+            read_text("zero", "version = 0\n").unwrap_err(),
+            "unknown bundle version 0"
+        );
+        assert_eq!(
+            read_text("none", "[data]\n").unwrap_err(),
+            "FILE is not a pomodoro bundle"
+        );
+        assert!(read_text("broken", "version = \"1\"\n")
+            .unwrap_err()
+            .starts_with("invalid bundle FILE"));
+    }
+
+    #[test]
+    fn read_refuses_settings_out_of_range() {
+        assert_eq!(
+            read_text(
+                "settings",
+                "version = 1\ncreated = \"\"\n\n[data.settings]\npomodoro = 1500\n\
+                 short_break = 0\nlong_break = 6000\npomodoros_per_long_break = 4\n",
+            )
+            .unwrap_err(),
+            "invalid bundle FILE: long_break must be between 1 and 99"
+        );
+    }
+
+    #[test]
+    fn read_reports_a_missing_file() {
+        let path = env::temp_dir().join("pomodoro-bundle-missing.toml");
+        assert!(read(&path).unwrap_err().starts_with("failed to read"));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::logging::{self, LOG_LEVEL_VAR};
+use log::LevelFilter;
+
+pub const USAGE: &str = "\
+Usage: pomodoro [OPTIONS]
+
+Options:
+      --log-level <LEVEL>  error, warn, info, debug, trace or off, $POMODORO_LOG otherwise
+      --preset <NAME>      Start with timer settings of a preset, e.g. deep-work
+  -h, --help               Print this help
+  -V, --version            Print version";
+
+#[derive(Debug, Default)]
+pub struct Args {
+    pub help: bool,
+    pub version: bool,
+    pub log_level: Option<LevelFilter>,
+    pub preset: Option<String>,
+}
+
+impl Args {
+    /// NOTE `--log-level`, then `$POMODORO_LOG`, then the default
+    pub fn log_level(&self) -> Result<LevelFilter, String> {
+        if let Some(level) = self.log_level {
+            return Ok(level);
+        }
+        match std::env::var(LOG_LEVEL_VAR) {
+            Ok(level) if !level.is_empty() => {
+                logging::parse_level(&level).map_err(|e| format!("{}: {}", LOG_LEVEL_VAR, e))
+            }
+            _ => Ok(logging::DEFAULT_LOG_LEVEL),
+        }
+    }
+}
+
+/// NOTE arguments without the program name, both `--opt value` and `--opt=value` are taken
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| match inline_value {
+            Some(value) => Ok(value.to_string()),
+            None => args
+                .next()
+                .ok_or_else(|| format!("`{}` needs a value", name)),
+        };
+        match name.as_str() {
+            "-h" | "--help" | "-V" | "--version" if inline_value.is_some() => {
+                return Err(format!("`{}` takes no value", name))
+            }
+            "-h" | "--help" => parsed.help = true,
+            "-V" | "--version" => parsed.version = true,
+            "--log-level" => parsed.log_level = Some(logging::parse_level(&value(&name)?)?),
+            "--preset" => parsed.preset = Some(value(&name)?),
+            _ => return Err(format!("unknown argument `{}`", arg)),
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Args, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_takes_values_both_ways() {
+        let args = parse_args(&["--log-level", "debug", "--preset=deep-work", "-V"]).unwrap();
+        assert_eq!(args.log_level, Some(LevelFilter::Debug));
+        assert_eq!(args.preset.as_deref(), Some("deep-work"));
+        assert!(args.version && !args.help);
+
+        let args = parse_args(&["--log-level=OFF", "--help"]).unwrap();
+        assert_eq!(args.log_level, Some(LevelFilter::Off));
+        assert!(args.help);
+        assert_eq!(args.log_level(), Ok(LevelFilter::Off));
+    }
+
+    #[test]
+    fn parse_rejects_bad_arguments() {
+        assert_eq!(
+            parse_args(&["--log-level"]).unwrap_err(),
+            "`--log-level` needs a value"
+        );
+        assert_eq!(
+            parse_args(&["--log-level", "loud"]).unwrap_err(),
+            "unknown log level `loud`"
+        );
+        assert_eq!(
+            parse_args(&["--help=yes"]).unwrap_err(),
+            "`--help` takes no value"
+        );
+        assert_eq!(parse_args(&["-x"]).unwrap_err(), "unknown argument `-x`");
+        // NOTE `=` only splits long options
+        assert_eq!(
+            parse_args(&["-h=1"]).unwrap_err(),
+            "unknown argument `-h=1`"
+        );
+    }
+}
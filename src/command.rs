@@ -0,0 +1,467 @@
+use crate::app::{App, InterruptionKind, Theme};
+use crate::backup::{self, ImportMode};
+use crate::custom_widgets::{LineEditor, TextInput};
+use crate::keybindings::ACTION_NAMES;
+use crate::storage;
+
+/// NOTE commands taking arguments, with their usage shown on wrong arguments
+const COMMANDS: [(&str, &str); 8] = [
+    (
+        "set",
+        "set <pomodoro|short_break|long_break|pomodoros_per_long_break> <value>",
+    ),
+    (
+        "task",
+        "task <add <title> [estimate] [notes]|edit|done|archive|delete|timer [preset] [<setting>=<value>...]>",
+    ),
+    ("theme", "theme <default|dark|light>"),
+    ("export", "export <file>"),
+    ("import", "import <file> [merge|replace]"),
+    ("report", "report <file>"),
+    ("preset", "preset <name>|preset save <name>"),
+    ("interruption", "interruption <internal|external> [note]"),
+];
+
+/// NOTE short names for actions, every action name is a command too
+const ALIASES: [(&str, &str); 7] = [
+    ("skip", "abort_timer"),
+    ("start", "launch_timer"),
+    ("pause", "pause_timer"),
+    ("toggle", "toggle_timer"),
+    ("help", "open_help"),
+    ("stats", "open_stats"),
+    ("q", "quit"),
+];
+
+const SET_FIELDS: [&str; 4] = [
+    "pomodoro",
+    "short_break",
+    "long_break",
+    "pomodoros_per_long_break",
+];
+
+const TASK_SUBCOMMANDS: [&str; 6] = ["add", "edit", "done", "archive", "delete", "timer"];
+
+/// NOTE text typed in command line, with completions for the word under cursor
+#[derive(Debug, Default)]
+pub struct CommandLine {
+    pub input: LineEditor,
+    pub completions: Vec<String>,
+    // NOTE completion shown in input, None while typing
+    pub selected: Option<usize>,
+    // NOTE input as typed before cycling through completions
+    typed: String,
+    // NOTE completed after `:preset`, kept when cleared
+    pub preset_names: Vec<String>,
+}
+
+impl CommandLine {
+    pub fn clear(&mut self) {
+        *self = Self {
+            preset_names: std::mem::take(&mut self.preset_names),
+            ..Self::default()
+        };
+    }
+
+    /// NOTE completions follow every edit of the input
+    pub fn edit<R>(&mut self, edit: impl FnOnce(&mut dyn TextInput) -> R) -> R {
+        let result = edit(&mut self.input);
+        self.refresh();
+        result
+    }
+
+    pub fn complete_next(&mut self) {
+        self.cycle(|selected, len| match selected {
+            Some(idx) if idx + 1 < len => Some(idx + 1),
+            Some(_) => None,
+            None => Some(0),
+        });
+    }
+
+    pub fn complete_prev(&mut self) {
+        self.cycle(|selected, len| match selected {
+            Some(0) => None,
+            Some(idx) => Some(idx - 1),
+            None => Some(len - 1),
+        });
+    }
+
+    /// NOTE going past either end of completions shows the typed input again
+    fn cycle(&mut self, step: impl Fn(Option<usize>, usize) -> Option<usize>) {
+        if self.completions.is_empty() {
+            return;
+        }
+        self.selected = step(self.selected, self.completions.len());
+        let line = match self.selected {
+            Some(idx) => replace_last_word(&self.typed, &self.completions[idx]),
+            None => self.typed.clone(),
+        };
+        self.input.set_text(&line);
+    }
+
+    fn refresh(&mut self) {
+        self.typed = self.input.text().to_string();
+        self.completions = completions(&self.typed, &self.preset_names);
+        self.selected = None;
+    }
+}
+
+fn replace_last_word(line: &str, word: &str) -> String {
+    let start = line.rfind(char::is_whitespace).map_or(0, |idx| idx + 1);
+    format!("{}{}", &line[..start], word)
+}
+
+/// NOTE score of `pattern` as a subsequence of `candidate`, case insensitive,
+/// higher is better, None if it does not match at all
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut pattern = pattern.chars().map(|c| c.to_ascii_lowercase()).peekable();
+    let mut prev_matched = false;
+    let mut prev_char = None;
+    for (idx, c) in candidate.chars().enumerate() {
+        let Some(&p) = pattern.peek() else {
+            break;
+        };
+        if c.to_ascii_lowercase() == p {
+            pattern.next();
+            score += 10;
+            // NOTE matches at word starts and right after another match count more
+            if idx == 0 || matches!(prev_char, Some('_') | Some(' ')) {
+                score += 20;
+            }
+            if prev_matched {
+                score += 15;
+            }
+            prev_matched = true;
+        } else {
+            score -= 1;
+            prev_matched = false;
+        }
+        prev_char = Some(c);
+    }
+    match pattern.peek() {
+        Some(_) => None,
+        None => Some(score),
+    }
+}
+
+fn fuzzy_sort<'a>(pattern: &str, candidates: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut matched: Vec<(i64, &str)> = candidates
+        .filter_map(|candidate| fuzzy_score(pattern, candidate).map(|score| (score, candidate)))
+        .collect();
+    matched.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then(a.cmp(b)));
+    matched.dedup_by(|(_, a), (_, b)| a == b);
+    matched
+        .into_iter()
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+fn command_names() -> impl Iterator<Item = &'static str> {
+    ACTION_NAMES
+        .keys()
+        .copied()
+        .chain(COMMANDS.iter().map(|(name, _)| *name))
+        .chain(ALIASES.iter().map(|(alias, _)| *alias))
+}
+
+/// NOTE candidates for the word being typed, best match first
+pub fn completions(line: &str, preset_names: &[String]) -> Vec<String> {
+    let words = split_words(line).unwrap_or_default();
+    let typing_new_word = line.is_empty() || line.ends_with(char::is_whitespace);
+    let (done, current) = match (typing_new_word, words.split_last()) {
+        (true, _) | (false, None) => (&words[..], ""),
+        (false, Some((last, done))) => (done, last.as_str()),
+    };
+    let candidates: Vec<&str> = match done {
+        [] => return fuzzy_sort(current, command_names()),
+        [command] if command == "set" => SET_FIELDS.to_vec(),
+        [command] if command == "task" => TASK_SUBCOMMANDS.to_vec(),
+        [command] if command == "theme" => Theme::NAMES.to_vec(),
+        [command, _file] if command == "import" => ImportMode::NAMES.to_vec(),
+        [command] if command == "interruption" => InterruptionKind::NAMES.to_vec(),
+        [command, subcommand] if command == "task" && subcommand == "timer" => {
+            preset_names.iter().map(String::as_str).collect()
+        }
+        [command] if command == "preset" => std::iter::once("save")
+            .chain(preset_names.iter().map(String::as_str))
+            .collect(),
+        _ => Vec::new(),
+    };
+    fuzzy_sort(current, candidates.into_iter())
+}
+
+/// Split a command line into words, double or single quotes keep spaces in a word
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err("unterminated quote".to_string());
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Run a command line, returns a message to show on success
+pub fn execute(app: &mut App, line: &str) -> Result<Option<String>, String> {
+    let words = split_words(line)?;
+    let Some((command, args)) = words.split_first() else {
+        return Ok(None);
+    };
+    let command = ALIASES
+        .iter()
+        .find(|(alias, _)| alias == command)
+        .map_or(command.as_str(), |(_, name)| *name);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match (command, args.as_slice()) {
+        ("set", [field, value]) => {
+            app.set_timer_field(field, value)?;
+            Ok(Some(format!("{} set to {}", field, value)))
+        }
+        ("task", ["add", title, rest @ ..]) => {
+            let (estimate, notes) = match rest {
+                [] => ("", String::new()),
+                [estimate, notes @ ..] => (*estimate, notes.join(" ")),
+            };
+            let title = app.add_task_from_text(title, &notes, estimate)?;
+            Ok(Some(format!("added task {}", title)))
+        }
+        ("task", ["edit"]) => {
+            app.edit_task();
+            Ok(None)
+        }
+        ("task", ["done"]) => {
+            app.toggle_task_done();
+            Ok(None)
+        }
+        ("task", ["archive"]) => {
+            app.archive_done_tasks();
+            Ok(None)
+        }
+        ("task", ["delete"]) => {
+            app.delete_task();
+            Ok(None)
+        }
+        ("task", ["timer", spec @ ..]) => app.set_task_timer(&spec.join(" ")).map(Some),
+        ("theme", [name]) => {
+            app.theme = Theme::from_name(name).ok_or(format!("unknown theme `{}`", name))?;
+            Ok(None)
+        }
+        ("export", [file]) => {
+            backup::export(&backup::expand_path(file), app.saved_data())?;
+            Ok(Some(format!("exported to {}", file)))
+        }
+        ("import", [file, mode @ ..]) if mode.len() <= 1 => {
+            let mode = match mode.first() {
+                None => ImportMode::Merge,
+                Some(name) => ImportMode::from_name(name)
+                    .ok_or(format!("unknown import mode `{}`, merge or replace", name))?,
+            };
+            let bundle = backup::read(&backup::expand_path(file))?;
+            // NOTE no snapshot, no import
+            let snapshot = backup::snapshot(app.saved_data(), "import")?;
+            let config = backup::import_config(&bundle, mode)?;
+            let count = app.import_data(bundle.data, mode);
+            log::info!(
+                "imported {} tasks from {}, snapshot at {}",
+                count,
+                file,
+                snapshot.display()
+            );
+            let mut message = format!(
+                "imported {} tasks, snapshot at {}",
+                count,
+                snapshot.display()
+            );
+            if !config.is_empty() {
+                message.push_str(&format!(", {} apply after restart", config.join(", ")));
+            }
+            Ok(Some(message))
+        }
+        ("report", [file]) => {
+            let path = backup::expand_path(file);
+            let mut text = app.stats_report().join("\n");
+            text.push('\n');
+            storage::write_file(&path, &text)
+                .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+            Ok(Some(format!("report written to {}", file)))
+        }
+        ("preset", ["save", name]) => {
+            let path = app.save_preset(name)?;
+            Ok(Some(format!("preset {} saved to {}", name, path.display())))
+        }
+        ("preset", [name]) => {
+            app.apply_preset(name)?;
+            Ok(None)
+        }
+        ("interruption", [kind, note @ ..]) => {
+            let kind = InterruptionKind::from_name(kind)
+                .ok_or_else(|| format!("unknown interruption `{}`", kind))?;
+            app.log_interruption(kind, &note.join(" ")).map(Some)
+        }
+        (name, []) => match ACTION_NAMES.get(name) {
+            Some(action) => {
+                action(app);
+                Ok(None)
+            }
+            None => Err(unknown_or_usage(name)),
+        },
+        (name, _) => Err(unknown_or_usage(name)),
+    }
+}
+
+fn unknown_or_usage(name: &str) -> String {
+    match COMMANDS.iter().find(|(command, _)| *command == name) {
+        Some((_, usage)) => format!("usage: {}", usage),
+        None if ACTION_NAMES.contains_key(name) => format!("{} takes no arguments", name),
+        None => match fuzzy_sort(name, command_names()).first() {
+            Some(guess) => format!("unknown command `{}`, did you mean `{}`?", name, guess),
+            None => format!("unknown command `{}`", name),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// NOTE debug builds start with sample tasks
+    fn app() -> App {
+        let mut app = App::default();
+        app.task_list.items.clear();
+        app
+    }
+
+    #[test]
+    fn split_words_keeps_quoted_spaces() {
+        assert_eq!(
+            split_words("  task add 'write tests'  2 \"a \"b"),
+            Ok(vec![
+                "task".to_string(),
+                "add".to_string(),
+                "write tests".to_string(),
+                "2".to_string(),
+                "a b".to_string()
+            ])
+        );
+        assert_eq!(split_words("''"), Ok(vec![String::new()]));
+        assert_eq!(split_words("   "), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn split_words_rejects_unterminated_quote() {
+        assert_eq!(
+            split_words("task add 'oops"),
+            Err("unterminated quote".to_string())
+        );
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts_and_runs() {
+        assert_eq!(fuzzy_score("xyz", "toggle_timer"), None);
+        assert_eq!(fuzzy_score("", "quit"), Some(0));
+        let start = fuzzy_score("tt", "toggle_timer").unwrap();
+        let middle = fuzzy_score("tt", "settings").unwrap();
+        assert!(start > middle);
+        assert!(fuzzy_score("TOG", "toggle_timer").is_some());
+    }
+
+    #[test]
+    fn completions_follow_the_command() {
+        assert_eq!(completions("them", &[])[0], "theme");
+        assert_eq!(completions("theme ", &[]), vec!["dark", "default", "light"]);
+        assert_eq!(completions("set short", &[]), vec!["short_break"]);
+        let presets = vec!["deep".to_string(), "quick".to_string()];
+        assert_eq!(
+            completions("preset ", &presets),
+            vec!["deep", "quick", "save"]
+        );
+        assert_eq!(completions("task timer q", &presets), vec!["quick"]);
+        assert!(completions("export ", &presets).is_empty());
+    }
+
+    #[test]
+    fn task_add_checks_title_and_estimate() {
+        let mut app = app();
+        assert_eq!(
+            execute(&mut app, "task add '  write tests  ' 3 some notes"),
+            Ok(Some("added task write tests".to_string()))
+        );
+        let task = app.task_list.items.last().unwrap();
+        assert_eq!(
+            (task.title(), task.notes.as_str()),
+            ("write tests", "some notes")
+        );
+        assert_eq!(task.pomodoros, 3);
+
+        assert_eq!(
+            execute(&mut app, "task add '   '"),
+            Err("title required".to_string())
+        );
+        assert_eq!(
+            execute(&mut app, "task add title 0"),
+            Err("estimate must be between 1 and 99 pomodoros".to_string())
+        );
+        assert_eq!(
+            execute(&mut app, "task add title many"),
+            Err("estimate `many` is not a number".to_string())
+        );
+        assert_eq!(app.task_list.items.len(), 1);
+    }
+
+    #[test]
+    fn task_add_caps_the_title() {
+        let mut app = app();
+        let title = "t".repeat(200);
+        execute(&mut app, &format!("task add {}", title)).unwrap();
+        let task = app.task_list.items.last().unwrap();
+        assert!(task.title().chars().count() < 200);
+        assert_eq!(task.pomodoros, 1);
+    }
+
+    #[test]
+    fn unknown_commands_get_a_guess_or_usage() {
+        let mut app = app();
+        assert_eq!(
+            execute(&mut app, "thme"),
+            Err("unknown command `thme`, did you mean `theme`?".to_string())
+        );
+        assert_eq!(
+            execute(&mut app, "zzz"),
+            Err("unknown command `zzz`".to_string())
+        );
+        assert_eq!(
+            execute(&mut app, "export"),
+            Err("usage: export <file>".to_string())
+        );
+        assert_eq!(
+            execute(&mut app, "quit now"),
+            Err("quit takes no arguments".to_string())
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use std::env;
+use std::path::PathBuf;
+
+const APP_DIR: &str = "pomodoro";
+
+/// NOTE `$XDG_CONFIG_HOME/pomodoro`, falls back to `~/.config/pomodoro`
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+pub fn keybindings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("keybindings.toml"))
+}
+
+/// NOTE `$XDG_DATA_HOME/pomodoro`, falls back to `~/.local/share/pomodoro`
+pub fn data_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// NOTE `$XDG_STATE_HOME/pomodoro`, falls back to `~/.local/state/pomodoro`
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    let base = match env::var_os(var) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(fallback),
+    };
+    Some(base.join(APP_DIR))
+}
@@ -0,0 +1,27 @@
+use crate::config;
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+
+/// NOTE `$XDG_STATE_HOME/pomodoro/crash-<time>.txt` with what panicked, where, and a backtrace
+pub fn write_report(info: &PanicHookInfo) -> io::Result<PathBuf> {
+    let dir = config::state_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+    fs::create_dir_all(&dir)?;
+    let now = chrono::Local::now();
+    let path = dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+    let thread = std::thread::current();
+    let report = format!(
+        "{} {} crashed at {}\nthread `{}` {}\n\n{}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        now.to_rfc3339(),
+        thread.name().unwrap_or("<unnamed>"),
+        info,
+        Backtrace::force_capture(),
+    );
+    fs::write(&path, report)?;
+    Ok(path)
+}
@@ -1,5 +1,10 @@
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
 use ratatui::widgets::ListState;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
+const DEFAULT_MAX_LEN: usize = 256;
 
 #[derive(Debug, Default)]
 pub struct StatefulList<T> {
@@ -8,20 +13,6 @@ pub struct StatefulList<T> {
 }
 
 impl<T> StatefulList<T> {
-    pub fn with_items(items: Vec<T>) -> StatefulList<T> {
-        if !items.is_empty() {
-            StatefulList {
-                state: ListState::default().with_selected(Some(0)),
-                items,
-            }
-        } else {
-            StatefulList {
-                state: ListState::default(),
-                items,
-            }
-        }
-    }
-
     pub fn next_entry(&mut self) {
         let i = match self.state.selected() {
             Some(i) => {
@@ -51,7 +42,644 @@ impl<T> StatefulList<T> {
         self.state.select(Some(i));
     }
 
+    pub fn select_first(&mut self) {
+        if !self.items.is_empty() {
+            self.state.select(Some(0));
+        }
+    }
+
+    pub fn select_last(&mut self) {
+        if let Some(last) = self.items.len().checked_sub(1) {
+            self.state.select(Some(last));
+        }
+    }
+
+    /// NOTE move selection down by `steps`, stopping at the last item
+    pub fn forward(&mut self, steps: usize) {
+        if let Some(last) = self.items.len().checked_sub(1) {
+            let i = self.state.selected().map_or(0, |i| i.saturating_add(steps));
+            self.state.select(Some(i.min(last)));
+        }
+    }
+
+    /// NOTE move selection up by `steps`, stopping at the first item
+    pub fn backward(&mut self, steps: usize) {
+        if !self.items.is_empty() {
+            let i = self.state.selected().map_or(0, |i| i.saturating_sub(steps));
+            self.state.select(Some(i));
+        }
+    }
+
     pub fn unselect(&mut self) {
         self.state.select(None);
     }
 }
+
+/// NOTE editing shared by single and multi line fields, so the same keys work on both
+pub trait TextInput {
+    /// NOTE false if the field does not accept `c` or is full
+    fn insert(&mut self, c: char) -> bool;
+    /// NOTE typed or pasted text
+    fn insert_str(&mut self, text: &str);
+    /// NOTE erase grapheme before cursor, false if there is none or lines would join past their limit
+    fn delete_back(&mut self) -> bool;
+    fn delete_forward(&mut self);
+    fn move_left(&mut self);
+    fn move_right(&mut self);
+    fn move_home(&mut self);
+    fn move_end(&mut self);
+    fn move_word_left(&mut self);
+    fn move_word_right(&mut self);
+    /// NOTE erase word before cursor and blanks after it, like `Ctrl-w` in a shell
+    fn delete_word_back(&mut self);
+    fn delete_to_start(&mut self);
+    fn delete_to_end(&mut self);
+    fn clear(&mut self);
+
+    /// NOTE single line fields have no line breaks
+    fn insert_newline(&mut self) {}
+
+    /// NOTE false when there is no line above to move to
+    fn move_up(&mut self) -> bool {
+        false
+    }
+
+    /// NOTE false when there is no line below to move to
+    fn move_down(&mut self) -> bool {
+        false
+    }
+}
+
+/// NOTE single line text field with a cursor, edited with readline style keys.
+/// Cursor and length work on graphemes so multi codepoint characters move as one
+#[derive(Debug, Clone)]
+pub struct LineEditor {
+    text: String,
+    // NOTE byte offset into text, always on a grapheme boundary
+    cursor: usize,
+    // NOTE in graphemes
+    max_len: usize,
+    accept: fn(char) -> bool,
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_LEN)
+    }
+}
+
+impl LineEditor {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            text: String::new(),
+            cursor: 0,
+            max_len,
+            accept: |c| !c.is_control(),
+        }
+    }
+
+    /// NOTE field taking digits only
+    pub fn numeric(max_len: usize) -> Self {
+        Self {
+            accept: |c| c.is_ascii_digit(),
+            ..Self::new(max_len)
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// NOTE replace text, dropping what the field does not accept, cursor goes to the end
+    pub fn set_text(&mut self, text: &str) {
+        self.clear();
+        self.insert_str(text);
+    }
+
+    /// NOTE text with the grapheme under cursor reversed, a space when cursor is at the end
+    pub fn spans(&self, show_cursor: bool) -> Vec<Span<'_>> {
+        if !show_cursor {
+            return vec![Span::raw(self.text.as_str())];
+        }
+        cursor_spans(&self.text, self.cursor)
+    }
+
+    /// NOTE graphemes before cursor, kept when moving between lines of a text area
+    fn column(&self) -> usize {
+        self.text[..self.cursor].graphemes(true).count()
+    }
+
+    fn set_column(&mut self, column: usize) {
+        self.cursor = self
+            .text
+            .grapheme_indices(true)
+            .nth(column)
+            .map_or(self.text.len(), |(idx, _)| idx);
+    }
+
+    /// NOTE remove text after cursor, it starts the next line of a text area
+    fn split_off(&mut self) -> String {
+        self.text.split_off(self.cursor)
+    }
+
+    /// NOTE append text without moving cursor, when lines of a text area are joined.
+    /// False and nothing appended if it would go past `max_len`
+    fn append(&mut self, text: &str) -> bool {
+        if self.text.graphemes(true).count() + text.graphemes(true).count() > self.max_len {
+            return false;
+        }
+        self.text.push_str(text);
+        true
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(idx, _)| idx)
+    }
+
+    fn next_boundary(&self) -> usize {
+        next_boundary(&self.text, self.cursor)
+    }
+
+    fn prev_word_start(&self) -> usize {
+        self.text[..self.cursor]
+            .split_word_bound_indices()
+            .rev()
+            .find(|(_, w)| !is_blank(w))
+            .map_or(0, |(idx, _)| idx)
+    }
+}
+
+impl TextInput for LineEditor {
+    fn insert(&mut self, c: char) -> bool {
+        if !(self.accept)(c) || self.text.graphemes(true).count() >= self.max_len {
+            return false;
+        }
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        true
+    }
+
+    /// NOTE line breaks and tabs become spaces
+    fn insert_str(&mut self, text: &str) {
+        for c in text.chars() {
+            let c = match c {
+                '\r' => continue,
+                '\n' | '\t' => ' ',
+                c => c,
+            };
+            self.insert(c);
+        }
+    }
+
+    fn delete_back(&mut self) -> bool {
+        let start = self.prev_boundary();
+        self.text.replace_range(start..self.cursor, "");
+        let deleted = start != self.cursor;
+        self.cursor = start;
+        deleted
+    }
+
+    fn delete_forward(&mut self) {
+        let end = self.next_boundary();
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    fn move_left(&mut self) {
+        self.cursor = self.prev_boundary();
+    }
+
+    fn move_right(&mut self) {
+        self.cursor = self.next_boundary();
+    }
+
+    fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    fn move_end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    fn move_word_left(&mut self) {
+        self.cursor = self.prev_word_start();
+    }
+
+    fn move_word_right(&mut self) {
+        let rest = &self.text[self.cursor..];
+        let mut words = rest
+            .split_word_bound_indices()
+            .skip_while(|(_, w)| is_blank(w));
+        self.cursor += match words.next() {
+            Some((idx, word)) => idx + word.len(),
+            None => rest.len(),
+        };
+    }
+
+    fn delete_word_back(&mut self) {
+        let start = self.prev_word_start();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    fn delete_to_start(&mut self) {
+        self.text.replace_range(..self.cursor, "");
+        self.cursor = 0;
+    }
+
+    fn delete_to_end(&mut self) {
+        self.text.truncate(self.cursor);
+    }
+
+    fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+}
+
+/// NOTE multi line text field, one line editor per line.
+/// Long lines wrap when rendered, and the view scrolls to keep cursor on screen
+#[derive(Debug, Clone)]
+pub struct TextArea {
+    lines: Vec<LineEditor>,
+    // NOTE line holding the cursor
+    row: usize,
+    max_lines: usize,
+    max_line_len: usize,
+    // NOTE first wrapped row on screen, updated when rendering
+    scroll: usize,
+}
+
+impl TextArea {
+    pub fn new(max_lines: usize, max_line_len: usize) -> Self {
+        Self {
+            lines: vec![LineEditor::new(max_line_len)],
+            row: 0,
+            max_lines,
+            max_line_len,
+            scroll: 0,
+        }
+    }
+
+    /// NOTE lines joined with `\n`
+    pub fn text(&self) -> String {
+        let lines: Vec<&str> = self.lines.iter().map(LineEditor::text).collect();
+        lines.join("\n")
+    }
+
+    /// NOTE replace text, cursor goes to the end
+    pub fn set_text(&mut self, text: &str) {
+        self.clear();
+        self.insert_str(text);
+    }
+
+    /// NOTE rows of text wrapped to `width`, scrolled so cursor row is among the `height` shown
+    pub fn render_lines(&mut self, width: u16, height: u16, show_cursor: bool) -> Vec<Line<'_>> {
+        let width = (width as usize).max(1);
+        let height = (height as usize).max(1);
+        // NOTE (line, start, end) byte ranges of every wrapped row
+        let mut rows: Vec<(usize, usize, usize)> = Vec::new();
+        let mut cursor_row = 0;
+        for (idx, line) in self.lines.iter().enumerate() {
+            let text = line.text();
+            let mut start = 0;
+            let mut row_width = 0;
+            for (offset, grapheme) in text.grapheme_indices(true) {
+                let grapheme_width = grapheme.width();
+                if row_width + grapheme_width > width && offset > start {
+                    rows.push((idx, start, offset));
+                    start = offset;
+                    row_width = 0;
+                }
+                row_width += grapheme_width;
+            }
+            // NOTE a full last row leaves no room for cursor after the text
+            if idx == self.row && line.cursor == text.len() && row_width >= width {
+                rows.push((idx, start, text.len()));
+                start = text.len();
+            }
+            rows.push((idx, start, text.len()));
+            if idx == self.row {
+                cursor_row = rows
+                    .iter()
+                    .rposition(|&(row, start, _)| row == idx && start <= line.cursor)
+                    .unwrap_or(rows.len() - 1);
+            }
+        }
+
+        if cursor_row < self.scroll {
+            self.scroll = cursor_row;
+        } else if cursor_row >= self.scroll + height {
+            self.scroll = cursor_row + 1 - height;
+        }
+        self.scroll = self.scroll.min(rows.len().saturating_sub(height));
+
+        rows.iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(height)
+            .map(|(row_idx, &(idx, start, end))| {
+                let text = &self.lines[idx].text()[start..end];
+                if show_cursor && row_idx == cursor_row {
+                    Line::from(cursor_spans(text, self.lines[idx].cursor - start))
+                } else {
+                    Line::from(text)
+                }
+            })
+            .collect()
+    }
+
+    fn line(&mut self) -> &mut LineEditor {
+        &mut self.lines[self.row]
+    }
+
+    /// NOTE move line `row + 1` to the end of `row`, cursor where they meet.
+    /// False and lines kept apart when they would go past `max_line_len` together
+    fn join(&mut self, row: usize) -> bool {
+        let (above, below) = self.lines.split_at_mut(row + 1);
+        let line = &mut above[row];
+        let end = line.text.len();
+        if !line.append(below[0].text()) {
+            return false;
+        }
+        line.cursor = end;
+        self.lines.remove(row + 1);
+        true
+    }
+}
+
+impl TextInput for TextArea {
+    fn insert(&mut self, c: char) -> bool {
+        match c {
+            '\n' => {
+                let full = self.lines.len() >= self.max_lines;
+                if !full {
+                    self.insert_newline();
+                }
+                !full
+            }
+            c => self.line().insert(c),
+        }
+    }
+
+    fn insert_str(&mut self, text: &str) {
+        for c in text.chars() {
+            let c = match c {
+                '\r' => continue,
+                '\t' => ' ',
+                c => c,
+            };
+            self.insert(c);
+        }
+    }
+
+    fn delete_back(&mut self) -> bool {
+        if self.line().delete_back() {
+            return true;
+        }
+        // NOTE at start of a line, join it to the one above if both fit in one
+        if self.row == 0 || !self.join(self.row - 1) {
+            return false;
+        }
+        self.row -= 1;
+        true
+    }
+
+    fn delete_forward(&mut self) {
+        let row = self.row;
+        if self.lines[row].cursor < self.lines[row].text.len() {
+            self.line().delete_forward();
+        } else if row + 1 < self.lines.len() {
+            self.join(row);
+        }
+    }
+
+    fn move_left(&mut self) {
+        if self.lines[self.row].cursor > 0 {
+            self.line().move_left();
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.line().move_end();
+        }
+    }
+
+    fn move_right(&mut self) {
+        let row = self.row;
+        if self.lines[row].cursor < self.lines[row].text.len() {
+            self.line().move_right();
+        } else if row + 1 < self.lines.len() {
+            self.row += 1;
+            self.line().move_home();
+        }
+    }
+
+    fn move_home(&mut self) {
+        self.line().move_home();
+    }
+
+    fn move_end(&mut self) {
+        self.line().move_end();
+    }
+
+    fn move_word_left(&mut self) {
+        self.line().move_word_left();
+    }
+
+    fn move_word_right(&mut self) {
+        self.line().move_word_right();
+    }
+
+    fn delete_word_back(&mut self) {
+        self.line().delete_word_back();
+    }
+
+    fn delete_to_start(&mut self) {
+        self.line().delete_to_start();
+    }
+
+    fn delete_to_end(&mut self) {
+        self.line().delete_to_end();
+    }
+
+    fn clear(&mut self) {
+        self.lines = vec![LineEditor::new(self.max_line_len)];
+        self.row = 0;
+        self.scroll = 0;
+    }
+
+    fn insert_newline(&mut self) {
+        if self.lines.len() >= self.max_lines {
+            return;
+        }
+        let rest = self.line().split_off();
+        let mut line = LineEditor::new(self.max_line_len);
+        // NOTE rest of a line always fits in a line of its own
+        line.append(&rest);
+        self.row += 1;
+        self.lines.insert(self.row, line);
+    }
+
+    fn move_up(&mut self) -> bool {
+        if self.row == 0 {
+            return false;
+        }
+        let column = self.lines[self.row].column();
+        self.row -= 1;
+        self.line().set_column(column);
+        true
+    }
+
+    fn move_down(&mut self) -> bool {
+        if self.row + 1 >= self.lines.len() {
+            return false;
+        }
+        let column = self.lines[self.row].column();
+        self.row += 1;
+        self.line().set_column(column);
+        true
+    }
+}
+
+/// NOTE `text` split around the grapheme at byte `cursor`, which is reversed
+fn cursor_spans(text: &str, cursor: usize) -> Vec<Span<'_>> {
+    let end = next_boundary(text, cursor);
+    let under_cursor = match &text[cursor..end] {
+        "" => " ",
+        grapheme => grapheme,
+    };
+    vec![
+        Span::raw(&text[..cursor]),
+        Span::styled(under_cursor, Style::new().reversed()),
+        Span::raw(&text[end..]),
+    ]
+}
+
+fn next_boundary(text: &str, cursor: usize) -> usize {
+    text[cursor..]
+        .graphemes(true)
+        .next()
+        .map_or(cursor, |g| cursor + g.len())
+}
+
+fn is_blank(word: &str) -> bool {
+    word.chars().all(|c| !c.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str) -> LineEditor {
+        let mut line = LineEditor::new(DEFAULT_MAX_LEN);
+        line.set_text(text);
+        line
+    }
+
+    #[test]
+    fn line_editor_stops_at_max_len_and_drops_what_it_does_not_accept() {
+        let mut line = LineEditor::new(3);
+        line.insert_str("ab\ncd");
+        assert_eq!(line.text(), "ab ");
+        assert!(!line.insert('x'));
+
+        let mut number = LineEditor::numeric(4);
+        number.insert_str("1a2-3");
+        assert_eq!(number.text(), "123");
+    }
+
+    #[test]
+    fn line_editor_moves_over_graphemes() {
+        let mut line = line("ae\u{301}b");
+        line.move_left();
+        line.move_left();
+        assert_eq!(line.column(), 1);
+        line.delete_forward();
+        assert_eq!(line.text(), "ab");
+        line.move_end();
+        assert!(line.delete_back());
+        line.move_home();
+        assert!(!line.delete_back());
+        assert_eq!(line.text(), "a");
+    }
+
+    #[test]
+    fn line_editor_edits_words() {
+        let mut line = line("write unit  tests");
+        line.delete_word_back();
+        assert_eq!(line.text(), "write unit  ");
+        line.move_home();
+        line.move_word_right();
+        assert_eq!(line.column(), 5);
+        line.delete_to_end();
+        assert_eq!(line.text(), "write");
+        line.move_word_left();
+        line.move_right();
+        line.delete_to_start();
+        assert_eq!(line.text(), "rite");
+    }
+
+    fn area(max_lines: usize, max_line_len: usize, text: &str) -> TextArea {
+        let mut area = TextArea::new(max_lines, max_line_len);
+        area.set_text(text);
+        area
+    }
+
+    #[test]
+    fn text_area_stops_at_max_lines() {
+        let mut area = area(2, 10, "one\ntwo");
+        assert!(!area.insert('\n'));
+        area.insert_newline();
+        area.insert_str("\tthree");
+        assert_eq!(area.text(), "one\ntwo three");
+    }
+
+    #[test]
+    fn text_area_joins_lines_at_their_ends() {
+        let mut area = area(3, 10, "one\ntwo");
+        area.move_home();
+        assert!(area.delete_back());
+        assert_eq!(area.text(), "onetwo");
+        assert_eq!(area.lines[0].column(), 3);
+
+        area.insert_newline();
+        area.move_up();
+        area.move_end();
+        area.delete_forward();
+        assert_eq!(area.text(), "onetwo");
+        assert!(!area.move_down());
+    }
+
+    #[test]
+    fn text_area_keeps_lines_apart_past_their_limit() {
+        let mut area = area(3, 5, "abc\ndef");
+        area.move_home();
+        assert!(!area.delete_back());
+        assert_eq!(area.text(), "abc\ndef");
+        assert_eq!(area.row, 1);
+
+        area.move_up();
+        area.move_end();
+        area.delete_forward();
+        assert_eq!(area.text(), "abc\ndef");
+    }
+
+    #[test]
+    fn text_area_keeps_column_between_lines() {
+        let mut area = area(3, 10, "short\nx\nlonger");
+        area.move_up();
+        area.move_up();
+        area.move_end();
+        area.move_left();
+        area.move_down();
+        assert_eq!(area.lines[1].column(), 1);
+        area.move_down();
+        assert_eq!(area.lines[2].column(), 1);
+        assert!(!area.move_down());
+    }
+}
@@ -0,0 +1,80 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FALLBACK_EDITOR: &str = "vi";
+// NOTE names taken by someone else before giving up
+const TEMP_FILE_ATTEMPTS: u32 = 16;
+
+/// NOTE `$VISUAL`, then `$EDITOR`, may carry arguments like `code --wait`
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| FALLBACK_EDITOR.to_string())
+}
+
+/// NOTE a new file only the user can read, never one that was there already,
+/// temp dir is shared with everyone else
+fn create_temp_file(text: &str) -> io::Result<PathBuf> {
+    for attempt in 0..TEMP_FILE_ATTEMPTS {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos());
+        let path = env::temp_dir().join(format!(
+            "pomodoro-notes-{}-{}-{}.md",
+            std::process::id(),
+            stamp,
+            attempt
+        ));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path);
+        match file {
+            Ok(mut file) => {
+                if let Err(e) = file.write_all(text.as_bytes()) {
+                    let _ = fs::remove_file(&path);
+                    return Err(e);
+                }
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "no free name for a temporary file",
+    ))
+}
+
+/// Let user edit `text` in their editor through a temporary file, terminal must be
+/// out of raw mode and alternate screen while this runs
+pub fn edit_text(text: &str) -> io::Result<String> {
+    let path = create_temp_file(text)?;
+
+    let command = editor_command();
+    let mut words = command.split_whitespace();
+    let program = words.next().unwrap_or(FALLBACK_EDITOR);
+    let status = Command::new(program).args(words).arg(&path).status();
+    // NOTE read back before removing, even a failed editor may have written the file
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    let status = status.map_err(|e| io::Error::new(e.kind(), format!("{}: {}", program, e)))?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "{} exited with {}",
+            program, status
+        )));
+    }
+    // NOTE editors end files with a line break that was not in the notes
+    Ok(edited?.trim_end_matches('\n').to_string())
+}
@@ -0,0 +1,166 @@
+use crate::logging::LogEntry;
+use crate::service::RemoteCommand;
+use crate::sync::lock;
+use crossterm::event;
+use signal_hook::consts::signal::{SIGCONT, SIGHUP, SIGINT, SIGTERM, SIGTSTP};
+use signal_hook::iterator::Signals;
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+// NOTE how long the input thread waits for input before checking whether it got paused,
+// so also the longest pausing it takes
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// NOTE everything the main loop wakes up for, besides deadlines it asks for itself
+#[derive(Debug)]
+pub enum Event {
+    Input(event::Event),
+    // NOTE terminal input can not be read any more, the app can not go on
+    InputError(io::Error),
+    // NOTE one of `HANDLED_SIGNALS`
+    Signal(i32),
+    // NOTE just written to log
+    Log(LogEntry),
+    // NOTE called for over D-Bus
+    Remote(RemoteCommand),
+}
+
+// NOTE quit, suspend and resume, their default actions would leave the terminal in raw mode
+pub const HANDLED_SIGNALS: [i32; 5] = [SIGINT, SIGTERM, SIGHUP, SIGTSTP, SIGCONT];
+
+/// NOTE events from every source come through one channel, the main loop sleeps on it
+pub struct Events {
+    sender: Sender<Event>,
+    receiver: Receiver<Event>,
+    input_gate: Arc<InputGate>,
+}
+
+/// NOTE stops the input thread while another program owns the terminal, so keys typed there
+/// are not stolen
+#[derive(Default)]
+struct InputGate {
+    state: Mutex<GateState>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct GateState {
+    paused: bool,
+    // NOTE input thread is reading the terminal
+    polling: bool,
+}
+
+impl InputGate {
+    fn wait<'a>(&self, state: MutexGuard<'a, GateState>) -> MutexGuard<'a, GateState> {
+        self.changed
+            .wait(state)
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// NOTE returns once the input thread stopped reading the terminal
+    fn pause(&self) {
+        let mut state = lock(&self.state);
+        state.paused = true;
+        while state.polling {
+            state = self.wait(state);
+        }
+    }
+
+    fn resume(&self) {
+        lock(&self.state).paused = false;
+        self.changed.notify_all();
+    }
+
+    /// NOTE on input thread, blocks while paused
+    fn start_polling(&self) {
+        let mut state = lock(&self.state);
+        while state.paused {
+            state = self.wait(state);
+        }
+        state.polling = true;
+    }
+
+    fn stop_polling(&self) {
+        lock(&self.state).polling = false;
+        self.changed.notify_all();
+    }
+}
+
+impl Events {
+    /// NOTE starts reading terminal input and catching signals on their own threads
+    pub fn new() -> io::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let events = Self {
+            sender,
+            receiver,
+            input_gate: Arc::default(),
+        };
+        let sender = events.sender();
+        let gate = Arc::clone(&events.input_gate);
+        thread::spawn(move || {
+            if let Err(e) = read_input(&sender, &gate) {
+                // NOTE main loop is gone when sending fails, nothing to report to
+                let _ = sender.send(Event::InputError(e));
+            }
+        });
+
+        let mut signals = Signals::new(HANDLED_SIGNALS)?;
+        let sender = events.sender();
+        thread::spawn(move || {
+            for signal in signals.forever() {
+                if sender.send(Event::Signal(signal)).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(events)
+    }
+
+    /// NOTE for other event sources to send through
+    pub fn sender(&self) -> Sender<Event> {
+        self.sender.clone()
+    }
+
+    /// NOTE wait for the next event, or until `timeout` passed, forever when None
+    pub fn next(&self, timeout: Option<Duration>) -> Option<Event> {
+        match timeout {
+            Some(timeout) => match self.receiver.recv_timeout(timeout) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+            },
+            // NOTE the sender kept here means the channel never disconnects
+            None => self.receiver.recv().ok(),
+        }
+    }
+
+    /// NOTE waits for input thread to let go of the terminal, call before handing it over
+    pub fn pause_input(&self) {
+        self.input_gate.pause();
+    }
+
+    pub fn resume_input(&self) {
+        self.input_gate.resume();
+    }
+}
+
+fn read_input(sender: &Sender<Event>, gate: &InputGate) -> io::Result<()> {
+    loop {
+        gate.start_polling();
+        let input = match event::poll(INPUT_POLL_INTERVAL) {
+            Ok(true) => event::read().map(Some),
+            Ok(false) => Ok(None),
+            Err(e) => Err(e),
+        };
+        // NOTE whatever was read here was typed before the terminal was handed over
+        gate.stop_polling();
+        if let Some(input) = input? {
+            if sender.send(Event::Input(input)).is_err() {
+                // NOTE main loop is gone
+                return Ok(());
+            }
+        }
+    }
+}
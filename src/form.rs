@@ -0,0 +1,386 @@
+use crate::custom_widgets::{LineEditor, TextArea, TextInput};
+
+/// NOTE what a field takes, and how it is checked before its form is applied
+#[derive(Debug)]
+pub enum FieldKind {
+    /// NOTE free text on one line
+    Text { max_len: usize, required: bool },
+    /// NOTE whole number in `min..=max`, empty is allowed when not required
+    Number {
+        min: u64,
+        max: u64,
+        unit: &'static str,
+        required: bool,
+    },
+    /// NOTE multi line text, shares what is left of the form area with other notes
+    Notes {
+        max_lines: usize,
+        max_line_len: usize,
+    },
+}
+
+/// NOTE one entry of a form schema, forms are built from a static list of these
+#[derive(Debug)]
+pub struct FieldSpec {
+    // NOTE used to read and write the field from code, and by `:set`
+    pub name: &'static str,
+    pub label: &'static str,
+    pub kind: FieldKind,
+}
+
+impl FieldSpec {
+    pub fn validate(&self, text: &str) -> Result<(), String> {
+        match self.kind {
+            FieldKind::Text { required, .. } if required && text.trim().is_empty() => {
+                Err("required".to_string())
+            }
+            FieldKind::Number { required, .. } if !required && text.is_empty() => Ok(()),
+            FieldKind::Number { min, max, unit, .. } => match text.parse::<u64>() {
+                Ok(value) if (min..=max).contains(&value) => Ok(()),
+                Ok(_) => Err(format!("must be between {} and {} {}", min, max, unit)),
+                Err(_) if text.is_empty() => Err("required".to_string()),
+                Err(_) => Err(format!("`{}` is not a number", text)),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn editor(&self) -> Editor {
+        match self.kind {
+            FieldKind::Text { max_len, .. } => Editor::Line(LineEditor::new(max_len)),
+            // NOTE one more digit than `max` has, so out of range values get an error
+            FieldKind::Number { max, .. } => {
+                Editor::Line(LineEditor::numeric(max.to_string().len() + 1))
+            }
+            FieldKind::Notes {
+                max_lines,
+                max_line_len,
+            } => Editor::Area(TextArea::new(max_lines, max_line_len)),
+        }
+    }
+
+    /// NOTE shown after the value
+    pub fn unit(&self) -> Option<&'static str> {
+        match self.kind {
+            FieldKind::Number { unit, .. } => Some(unit),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Editor {
+    Line(LineEditor),
+    Area(TextArea),
+}
+
+impl Editor {
+    fn input_mut(&mut self) -> &mut dyn TextInput {
+        match self {
+            Editor::Line(line) => line,
+            Editor::Area(area) => area,
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Editor::Line(line) => line.text().to_string(),
+            Editor::Area(area) => area.text(),
+        }
+    }
+
+    fn set_text(&mut self, text: &str) {
+        match self {
+            Editor::Line(line) => line.set_text(text),
+            Editor::Area(area) => area.set_text(text),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Field {
+    pub spec: &'static FieldSpec,
+    pub editor: Editor,
+    // NOTE why the value was rejected by last `validate`
+    pub error: Option<String>,
+    // NOTE text when the form was last applied or loaded, edits differing from it are unsaved
+    saved: String,
+}
+
+impl Field {
+    pub fn is_modified(&self) -> bool {
+        self.editor.text() != self.saved
+    }
+}
+
+/// NOTE input panel generated from a schema: fields, navigation between them,
+/// validation and unsaved edits tracking
+#[derive(Debug)]
+pub struct Form {
+    fields: Vec<Field>,
+    selected: usize,
+}
+
+impl Form {
+    pub fn new(schema: &'static [FieldSpec]) -> Self {
+        let fields = schema
+            .iter()
+            .map(|spec| Field {
+                spec,
+                editor: spec.editor(),
+                error: None,
+                saved: String::new(),
+            })
+            .collect();
+        Self {
+            fields,
+            selected: 0,
+        }
+    }
+
+    /// NOTE fill fields by name, the values count as saved
+    pub fn with_values(mut self, values: &[(&str, &str)]) -> Self {
+        for (name, value) in values.iter() {
+            self.set_text(name, value);
+        }
+        self.mark_saved();
+        self
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// NOTE mutable as notes scroll when rendered
+    pub fn fields_mut(&mut self) -> &mut [Field] {
+        &mut self.fields
+    }
+
+    pub fn is_selected(&self, idx: usize) -> bool {
+        self.selected == idx
+    }
+
+    pub fn select_next_field(&mut self) {
+        self.selected = (self.selected + 1) % self.fields.len();
+    }
+
+    pub fn select_prev_field(&mut self) {
+        self.selected = (self.selected + self.fields.len() - 1) % self.fields.len();
+    }
+
+    /// NOTE out of range `idx` is ignored
+    pub fn select_field(&mut self, idx: usize) {
+        if idx < self.fields.len() {
+            self.selected = idx;
+        }
+    }
+
+    pub fn get_field_mut(&mut self) -> &mut dyn TextInput {
+        self.fields[self.selected].editor.input_mut()
+    }
+
+    pub fn spec(&self, name: &str) -> Option<&'static FieldSpec> {
+        self.fields
+            .iter()
+            .find(|field| field.spec.name == name)
+            .map(|field| field.spec)
+    }
+
+    fn field_mut(&mut self, name: &str) -> &mut Field {
+        self.fields
+            .iter_mut()
+            .find(|field| field.spec.name == name)
+            .unwrap_or_else(|| panic!("no field `{}` in form", name))
+    }
+
+    fn field(&self, name: &str) -> &Field {
+        self.fields
+            .iter()
+            .find(|field| field.spec.name == name)
+            .unwrap_or_else(|| panic!("no field `{}` in form", name))
+    }
+
+    pub fn text(&self, name: &str) -> String {
+        self.field(name).editor.text()
+    }
+
+    /// NOTE None if the field is empty or not a number
+    pub fn number(&self, name: &str) -> Option<u64> {
+        self.text(name).parse().ok()
+    }
+
+    pub fn set_text(&mut self, name: &str, text: &str) {
+        self.field_mut(name).editor.set_text(text);
+    }
+
+    /// NOTE set errors beside rejected fields, true if there is none
+    pub fn validate(&mut self) -> bool {
+        for field in self.fields.iter_mut() {
+            field.error = field.spec.validate(&field.editor.text()).err();
+        }
+        self.fields.iter().all(|field| field.error.is_none())
+    }
+
+    /// NOTE for checks that need more than the field spec
+    pub fn set_error(&mut self, name: &str, error: String) {
+        self.field_mut(name).error = Some(error);
+    }
+
+    /// NOTE every field is in use now
+    pub fn mark_saved(&mut self) {
+        for field in self.fields.iter_mut() {
+            field.saved = field.editor.text();
+            field.error = None;
+        }
+    }
+
+    /// NOTE one field is in use now, others keep their unsaved edits
+    pub fn mark_field_saved(&mut self, name: &str) {
+        let field = self.field_mut(name);
+        field.saved = field.editor.text();
+        field.error = None;
+    }
+
+    /// NOTE some field differs from what is in use
+    pub fn is_modified(&self) -> bool {
+        self.fields.iter().any(Field::is_modified)
+    }
+
+    /// NOTE empty every field, and select the first one
+    pub fn clear(&mut self) {
+        for field in self.fields.iter_mut() {
+            field.editor.input_mut().clear();
+            field.error = None;
+            field.saved.clear();
+        }
+        self.selected = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static FIELDS: [FieldSpec; 3] = [
+        FieldSpec {
+            name: "title",
+            label: "Title: ",
+            kind: FieldKind::Text {
+                max_len: 8,
+                required: true,
+            },
+        },
+        FieldSpec {
+            name: "count",
+            label: "Count: ",
+            kind: FieldKind::Number {
+                min: 1,
+                max: 9,
+                unit: "times",
+                required: false,
+            },
+        },
+        FieldSpec {
+            name: "notes",
+            label: "Notes:",
+            kind: FieldKind::Notes {
+                max_lines: 2,
+                max_line_len: 8,
+            },
+        },
+    ];
+
+    fn error(form: &Form, name: &str) -> Option<String> {
+        form.fields()
+            .iter()
+            .find(|field| field.spec.name == name)
+            .and_then(|field| field.error.clone())
+    }
+
+    #[test]
+    fn field_spec_checks_text_and_numbers() {
+        let (title, count) = (&FIELDS[0], &FIELDS[1]);
+        assert_eq!(title.validate("  "), Err("required".to_string()));
+        assert_eq!(title.validate("a"), Ok(()));
+        assert_eq!(count.validate(""), Ok(()));
+        assert_eq!(count.validate("9"), Ok(()));
+        assert_eq!(
+            count.validate("10"),
+            Err("must be between 1 and 9 times".to_string())
+        );
+        assert_eq!(count.validate("x"), Err("`x` is not a number".to_string()));
+        assert_eq!(FIELDS[2].validate(""), Ok(()));
+    }
+
+    #[test]
+    fn required_number_rejects_empty() {
+        let spec = FieldSpec {
+            name: "length",
+            label: "Length: ",
+            kind: FieldKind::Number {
+                min: 1,
+                max: 9,
+                unit: "min",
+                required: true,
+            },
+        };
+        assert_eq!(spec.validate(""), Err("required".to_string()));
+    }
+
+    #[test]
+    fn validate_marks_rejected_fields_until_saved() {
+        let mut form = Form::new(&FIELDS).with_values(&[("count", "2")]);
+        assert!(!form.validate());
+        assert_eq!(error(&form, "title"), Some("required".to_string()));
+        assert_eq!(error(&form, "count"), None);
+
+        form.set_text("title", "write");
+        assert!(form.validate());
+        form.set_error("count", "too many".to_string());
+        assert_eq!(error(&form, "count"), Some("too many".to_string()));
+        form.mark_field_saved("count");
+        assert_eq!(error(&form, "count"), None);
+    }
+
+    #[test]
+    fn edits_count_as_unsaved_until_marked() {
+        let mut form = Form::new(&FIELDS).with_values(&[("title", "write")]);
+        assert!(!form.is_modified());
+        form.set_text("title", "read");
+        form.set_text("notes", "a\nb");
+        assert!(form.is_modified());
+        form.mark_field_saved("title");
+        assert!(form.is_modified());
+        form.mark_saved();
+        assert!(!form.is_modified());
+        assert_eq!(form.text("notes"), "a\nb");
+        assert_eq!(form.number("count"), None);
+    }
+
+    #[test]
+    fn number_fields_take_digits_only() {
+        let mut form = Form::new(&FIELDS);
+        form.select_field(1);
+        form.get_field_mut().insert_str("1x23");
+        assert_eq!(form.text("count"), "12");
+        assert_eq!(form.number("count"), Some(12));
+        assert!(!form.validate());
+    }
+
+    #[test]
+    fn selection_wraps_and_clear_starts_over() {
+        let mut form = Form::new(&FIELDS);
+        form.select_prev_field();
+        assert!(form.is_selected(2));
+        form.select_next_field();
+        assert!(form.is_selected(0));
+        form.select_field(5);
+        assert!(form.is_selected(0));
+
+        form.select_field(1);
+        form.set_text("title", "write");
+        form.clear();
+        assert!(form.is_selected(0));
+        assert_eq!(form.text("title"), "");
+    }
+}
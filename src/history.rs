@@ -0,0 +1,197 @@
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// NOTE a pomodoro that ran to its end or was skipped, kept in data file for statistics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomodoroRecord {
+    // NOTE RFC 3339, local time
+    pub ended: String,
+    // NOTE seconds the timer ran, pauses left out
+    pub length: u64,
+    // NOTE false when skipped before its end
+    pub finished: bool,
+    // NOTE the task it counted against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<u64>,
+    #[serde(default)]
+    pub internal: u64,
+    #[serde(default)]
+    pub external: u64,
+}
+
+/// NOTE what the pomodoros of a period or a task add up to
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Totals {
+    pub finished: u64,
+    pub skipped: u64,
+    pub seconds: u64,
+    pub internal: u64,
+    pub external: u64,
+}
+
+impl Totals {
+    fn add(&mut self, record: &PomodoroRecord) {
+        if record.finished {
+            self.finished += 1;
+        } else {
+            self.skipped += 1;
+        }
+        self.seconds += record.length;
+        self.internal += record.internal;
+        self.external += record.external;
+    }
+}
+
+/// NOTE day a record ended on, None for a stamp that does not parse
+fn ended_on(record: &PomodoroRecord) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(&record.ended)
+        .ok()
+        .map(|ended| ended.with_timezone(&Local).date_naive())
+}
+
+/// NOTE totals for today, this week (from Monday) and all time
+pub fn period_totals(records: &[PomodoroRecord], today: NaiveDate) -> [(&'static str, Totals); 3] {
+    let week_start = today - Days::new(today.weekday().num_days_from_monday() as u64);
+    let mut periods = [
+        ("today", Totals::default()),
+        ("this week", Totals::default()),
+        ("all time", Totals::default()),
+    ];
+    for record in records {
+        let day = ended_on(record);
+        if day == Some(today) {
+            periods[0].1.add(record);
+        }
+        if day.is_some_and(|day| week_start <= day && day <= today) {
+            periods[1].1.add(record);
+        }
+        periods[2].1.add(record);
+    }
+    periods
+}
+
+/// NOTE totals by the task they counted against, in order of first record
+pub fn task_totals(records: &[PomodoroRecord]) -> Vec<(Option<u64>, Totals)> {
+    let mut tasks: Vec<(Option<u64>, Totals)> = Vec::new();
+    for record in records {
+        match tasks.iter_mut().find(|(id, _)| *id == record.task_id) {
+            Some((_, totals)) => totals.add(record),
+            None => {
+                let mut totals = Totals::default();
+                totals.add(record);
+                tasks.push((record.task_id, totals));
+            }
+        }
+    }
+    tasks
+}
+
+fn totals_line(name: &str, totals: &Totals) -> String {
+    format!(
+        "{:<24} {:>8} {:>8} {:>8} {:>8} {:>8}",
+        name,
+        totals.finished,
+        totals.skipped,
+        totals.seconds / 60,
+        totals.internal,
+        totals.external
+    )
+}
+
+/// NOTE lines shown in stats page and written by `:report`, `title` names a task by its id
+pub fn report<'a>(
+    records: &[PomodoroRecord],
+    today: NaiveDate,
+    title: impl Fn(u64) -> Option<&'a str>,
+) -> Vec<String> {
+    let header = format!(
+        "{:<24} {:>8} {:>8} {:>8} {:>8} {:>8}",
+        "", "finished", "skipped", "minutes", "internal", "external"
+    );
+    let mut lines = vec![
+        format!("Pomodoros up to {}", today),
+        String::new(),
+        header.clone(),
+    ];
+    for (name, totals) in period_totals(records, today).iter() {
+        lines.push(totals_line(name, totals));
+    }
+    lines.extend([String::new(), "By task".to_string(), header]);
+    for (id, totals) in task_totals(records).iter() {
+        let name = match id {
+            Some(id) => match title(*id) {
+                Some(title) => title.chars().take(24).collect(),
+                None => format!("(deleted task {})", id),
+            },
+            None => "(no task)".to_string(),
+        };
+        lines.push(totals_line(&name, totals));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(ended: &str, finished: bool, task_id: Option<u64>, internal: u64) -> PomodoroRecord {
+        PomodoroRecord {
+            ended: ended.to_string(),
+            length: 1500,
+            finished,
+            task_id,
+            internal,
+            external: 0,
+        }
+    }
+
+    fn local(date: NaiveDate, hour: u32) -> String {
+        date.and_hms_opt(hour, 0, 0)
+            .and_then(|time| time.and_local_timezone(Local).single())
+            .map(|time| time.to_rfc3339())
+            .unwrap()
+    }
+
+    #[test]
+    fn periods_split_by_day_and_week() {
+        // NOTE a Wednesday
+        let today = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let records = [
+            record(&local(today, 10), true, Some(1), 2),
+            record(&local(today - Days::new(2), 10), false, Some(1), 1),
+            record(&local(today - Days::new(3), 10), true, None, 0),
+            record("not a date", true, None, 4),
+        ];
+        let periods = period_totals(&records, today);
+        assert_eq!((periods[0].1.finished, periods[0].1.internal), (1, 2));
+        assert_eq!((periods[1].1.finished, periods[1].1.skipped), (1, 1));
+        assert_eq!(periods[1].1.internal, 3);
+        assert_eq!((periods[2].1.finished, periods[2].1.internal), (3, 7));
+        assert_eq!(periods[2].1.seconds, 4 * 1500);
+    }
+
+    #[test]
+    fn tasks_keep_order_of_first_record() {
+        let records = [
+            record("", true, Some(2), 0),
+            record("", true, None, 0),
+            record("", false, Some(2), 1),
+        ];
+        let tasks = task_totals(&records);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].0, Some(2));
+        assert_eq!((tasks[0].1.finished, tasks[0].1.skipped), (1, 1));
+        assert_eq!(tasks[1].0, None);
+    }
+
+    #[test]
+    fn report_names_deleted_tasks() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let records = [record("", true, Some(1), 0), record("", true, Some(2), 0)];
+        let lines = report(&records, today, |id| (id == 1).then_some("write report"));
+        assert!(lines.iter().any(|line| line.starts_with("write report")));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("(deleted task 2)")));
+    }
+}
@@ -0,0 +1,264 @@
+use crate::config;
+use serde::Deserialize;
+use std::fs;
+use std::io::{self, Read};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+// NOTE how often a running hook is checked on
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// NOTE only the start of a failed hook's stderr goes into the log
+const STDERR_LIMIT: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    PomodoroStart,
+    PomodoroEnd,
+    BreakStart,
+    BreakEnd,
+    Pause,
+    Resume,
+    Skip,
+    TaskCompleted,
+}
+
+impl HookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::PomodoroStart => "pomodoro_start",
+            HookEvent::PomodoroEnd => "pomodoro_end",
+            HookEvent::BreakStart => "break_start",
+            HookEvent::BreakEnd => "break_end",
+            HookEvent::Pause => "pause",
+            HookEvent::Resume => "resume",
+            HookEvent::Skip => "skip",
+            HookEvent::TaskCompleted => "task_completed",
+        }
+    }
+}
+
+/// NOTE `$XDG_CONFIG_HOME/pomodoro/hooks.toml`, a shell command per event,
+/// events without one are left alone:
+///
+/// ```toml
+/// # seconds before a hook still running is killed
+/// timeout = 10
+/// pomodoro_start = "makoctl mode -a do-not-disturb"
+/// pomodoro_end = "makoctl mode -r do-not-disturb"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HooksFile {
+    timeout: Option<u64>,
+    pomodoro_start: Option<String>,
+    pomodoro_end: Option<String>,
+    break_start: Option<String>,
+    break_end: Option<String>,
+    pause: Option<String>,
+    resume: Option<String>,
+    skip: Option<String>,
+    task_completed: Option<String>,
+}
+
+/// NOTE what a hook is told about, through `POMODORO_*` environment variables
+#[derive(Debug, Default)]
+pub struct HookContext {
+    // NOTE `pomodoro`, `short_break` or `long_break`
+    pub phase: &'static str,
+    pub phase_length: Duration,
+    pub time_left: Duration,
+    pub task: Option<HookTask>,
+}
+
+#[derive(Debug)]
+pub struct HookTask {
+    pub id: u64,
+    pub title: String,
+    pub completed: u64,
+    pub pomodoros: u64,
+}
+
+impl HookContext {
+    fn vars(&self, event: HookEvent) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("POMODORO_EVENT", event.name().to_string()),
+            ("POMODORO_PHASE", self.phase.to_string()),
+            (
+                "POMODORO_PHASE_LENGTH",
+                self.phase_length.as_secs().to_string(),
+            ),
+            ("POMODORO_TIME_LEFT", self.time_left.as_secs().to_string()),
+        ];
+        if let Some(ref task) = self.task {
+            vars.extend([
+                ("POMODORO_TASK_ID", task.id.to_string()),
+                ("POMODORO_TASK", task.title.clone()),
+                ("POMODORO_TASK_COMPLETED", task.completed.to_string()),
+                ("POMODORO_TASK_ESTIMATE", task.pomodoros.to_string()),
+            ]);
+        }
+        vars
+    }
+}
+
+/// NOTE a hook waiting for its turn
+#[derive(Debug)]
+struct Job {
+    event: HookEvent,
+    command: String,
+    vars: Vec<(&'static str, String)>,
+    timeout: Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct Hooks {
+    file: HooksFile,
+    // NOTE None without a hooks file
+    sender: Option<Sender<Job>>,
+}
+
+impl Hooks {
+    fn command(&self, event: HookEvent) -> Option<&str> {
+        let file = &self.file;
+        match event {
+            HookEvent::PomodoroStart => file.pomodoro_start.as_deref(),
+            HookEvent::PomodoroEnd => file.pomodoro_end.as_deref(),
+            HookEvent::BreakStart => file.break_start.as_deref(),
+            HookEvent::BreakEnd => file.break_end.as_deref(),
+            HookEvent::Pause => file.pause.as_deref(),
+            HookEvent::Resume => file.resume.as_deref(),
+            HookEvent::Skip => file.skip.as_deref(),
+            HookEvent::TaskCompleted => file.task_completed.as_deref(),
+        }
+        .filter(|command| !command.trim().is_empty())
+    }
+
+    fn timeout(&self) -> Duration {
+        self.file
+            .timeout
+            .map_or(DEFAULT_TIMEOUT, Duration::from_secs)
+    }
+
+    /// NOTE queues the event's command for the hooks thread, so a hook started by a pause
+    /// finishes before the one started by the resume after it
+    pub fn run(&self, event: HookEvent, context: &HookContext) {
+        let (Some(command), Some(sender)) = (self.command(event), self.sender.as_ref()) else {
+            return;
+        };
+        let job = Job {
+            event,
+            command: command.to_string(),
+            vars: context.vars(event),
+            timeout: self.timeout(),
+        };
+        if sender.send(job).is_err() {
+            log::error!("hooks thread is gone, {} hook not run", event.name());
+        }
+    }
+}
+
+/// NOTE runs hooks one at a time with `sh -c`, failures and timeouts go into the log
+fn work(jobs: Receiver<Job>) {
+    for job in jobs {
+        let name = job.event.name();
+        log::debug!("running {} hook: {}", name, job.command);
+        match run_command(&job.command, job.vars, job.timeout) {
+            Ok(()) => log::debug!("{} hook done", name),
+            Err(e) => log::warn!("{} hook `{}` {}", name, job.command, e),
+        }
+    }
+}
+
+fn run_command(
+    command: &str,
+    vars: Vec<(&'static str, String)>,
+    timeout: Duration,
+) -> Result<(), String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(vars)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        // NOTE group of its own, so a timeout kills what the shell started too
+        .process_group(0)
+        .spawn()
+        .map_err(|e| format!("failed to start: {}", e))?;
+    // NOTE read on its own thread, a hook writing a lot would block on a full pipe otherwise
+    let stderr = child.stderr.take().map(|mut stderr| {
+        thread::spawn(move || {
+            let mut text = String::new();
+            let _ = stderr.read_to_string(&mut text);
+            text
+        })
+    });
+    let status = match wait_timeout(&mut child, timeout) {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            // NOTE group id is the shell's pid, already gone if it exited just now
+            if let Ok(pgid) = libc::pid_t::try_from(child.id()) {
+                unsafe {
+                    libc::kill(-pgid, libc::SIGKILL);
+                }
+            }
+            let _ = child.wait();
+            return Err(format!("timed out after {}s", timeout.as_secs()));
+        }
+        Err(e) => return Err(format!("failed to wait: {}", e)),
+    };
+    if status.success() {
+        return Ok(());
+    }
+    let stderr = stderr
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+    let stderr: String = stderr.trim().chars().take(STDERR_LIMIT).collect();
+    match stderr.is_empty() {
+        true => Err(format!("failed with {}", status)),
+        false => Err(format!("failed with {}: {}", status, stderr)),
+    }
+}
+
+/// NOTE None if `child` is still running after `timeout`
+fn wait_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+pub fn hooks_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("hooks.toml"))
+}
+
+/// NOTE no hooks when there is no hooks file
+pub fn load(path: &Path) -> Result<Hooks, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Hooks::default()),
+        Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+    };
+    let file = toml::from_str(&text)
+        .map_err(|e| format!("invalid hooks file {}: {}", path.display(), e))?;
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name("hooks".to_string())
+        .spawn(move || work(receiver))
+        .map_err(|e| format!("failed to start hooks thread: {}", e))?;
+    Ok(Hooks {
+        file,
+        sender: Some(sender),
+    })
+}
@@ -1,68 +1,161 @@
-use crate::app::{App, Tabs};
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
-use std::io;
+use crate::app::{App, Mode, Page};
+use crate::keybindings::{Keymap, Keymaps, SEQUENCE_TIMEOUT};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::Rect;
+use std::time::{Duration, Instant};
 
-use crate::app::AppAction;
-use std::collections::HashMap;
-
-// TODO invalid input feedback
-pub fn handle_events(
-    app: &mut App,
-    timer_setting_tab_keymap: &HashMap<KeyEvent, AppAction>,
-    task_manager_keymap: &HashMap<KeyEvent, AppAction>,
-) -> io::Result<()> {
-    if event::poll(std::time::Duration::from_millis(50))? {
-        match event::read()? {
-            Event::Key(key) => {
-                return handle_key(key, app, timer_setting_tab_keymap, task_manager_keymap);
-            }
-            Event::FocusGained => {}
-            Event::FocusLost => {}
-            Event::Mouse(_event) => {}
-            Event::Paste(_data) => {
-                // println!("{:?}", data);
-            }
-            Event::Resize(_width, _height) => {
-                // println!("New size {}x{}", width, height);
+/// NOTE false when the event can not have changed anything on screen
+pub fn handle_event(event: Event, app: &mut App, keymaps: &Keymaps) -> bool {
+    match event {
+        Event::Key(key) => handle_key(key, app, keymaps),
+        Event::FocusGained | Event::FocusLost => return false,
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Moved,
+            ..
+        }) => return false,
+        Event::Mouse(event) => handle_mouse(event, app),
+        Event::Paste(data) => {
+            if let Page::Normal = app.page_selected {
+                app.paste_text(&data);
             }
         }
+        // NOTE nothing to do besides drawing again at the new size
+        Event::Resize(_width, _height) => {}
+    }
+    true
+}
+
+fn current_keymap<'a>(app: &App, keymaps: &'a Keymaps) -> &'a Keymap {
+    match app.page_selected {
+        Page::Help | Page::Log | Page::Stats => &keymaps.help_page,
+        Page::Normal => keymaps.for_mode(app.mode),
     }
-    Ok(())
 }
 
-fn handle_key(
-    key: KeyEvent,
-    app: &mut App,
-    tiemr_setting_keymap: &HashMap<KeyEvent, AppAction>,
-    task_manager_keymap: &HashMap<KeyEvent, AppAction>,
-) -> io::Result<()> {
-    match app.tab_selected {
-        Tabs::TaskManager => {
-            if let Some(action) = task_manager_keymap.get(&key) {
-                action(app);
-            } else {
-                // NOTE these keys has no special mapping
-                // if they are valid setting input, we push them to screen
-                if let KeyCode::Char(code) = key.code {
-                    if code.is_alphanumeric() {
-                        app.push_user_input_field(code);
-                    }
+fn handle_key(key: KeyEvent, app: &mut App, keymaps: &Keymaps) {
+    // NOTE digits are counts for the next command in Normal and Visual mode,
+    // `0` only after another digit
+    let takes_count = matches!(app.page_selected, Page::Normal)
+        && matches!(app.mode, Mode::Normal | Mode::Visual);
+    if takes_count && app.pending_keys.is_empty() {
+        if let (KeyCode::Char(code), KeyModifiers::NONE) = (key.code, key.modifiers) {
+            if let Some(digit) = code.to_digit(10) {
+                if digit != 0 || app.count.is_some() {
+                    app.push_count_digit(digit as usize);
+                    return;
                 }
             }
         }
-        Tabs::PomodoroSetting => {
-            if let Some(action) = tiemr_setting_keymap.get(&key) {
-                action(app);
-            } else {
-                // NOTE these keys has no special mapping
-                // if they are valid setting input, we push them to screen
-                if let KeyCode::Char(code) = key.code {
-                    if code.is_ascii_digit() {
-                        app.push_user_input_field(code);
-                    }
-                }
+    }
+
+    app.pending_keys.push(key);
+    let keymap = current_keymap(app, keymaps);
+    // NOTE wait for more keys while a longer binding starts with the keys typed so far,
+    // an exact match is run when the sequence times out
+    let is_prefix = keymap
+        .keys()
+        .any(|keys| keys.len() > app.pending_keys.len() && keys.starts_with(&app.pending_keys));
+    if is_prefix {
+        app.pending_since = Some(Instant::now());
+        return;
+    }
+    finish_sequence(app, keymaps);
+}
+
+/// NOTE run the pending key sequence once it has waited long enough for its next key
+pub fn handle_sequence_timeout(app: &mut App, keymaps: &Keymaps) {
+    if let Some(since) = app.pending_since {
+        if since.elapsed() >= SEQUENCE_TIMEOUT {
+            finish_sequence(app, keymaps);
+        }
+    }
+}
+
+/// NOTE how long the pending key sequence may still wait for its next key
+pub fn sequence_time_left(app: &App) -> Option<Duration> {
+    app.pending_since
+        .map(|since| SEQUENCE_TIMEOUT.saturating_sub(since.elapsed()))
+}
+
+fn finish_sequence(app: &mut App, keymaps: &Keymaps) {
+    let keys = std::mem::take(&mut app.pending_keys);
+    app.pending_since = None;
+    match current_keymap(app, keymaps).get(&keys) {
+        Some(action) => action(app),
+        None => {
+            // NOTE in Insert mode and command line keys that are not bound are typed in
+            if matches!(app.page_selected, Page::Normal)
+                && matches!(app.mode, Mode::Insert | Mode::Command)
+            {
+                keys.iter().for_each(|key| insert_key(app, key));
             }
         }
     }
-    Ok(())
+    app.count = None;
+}
+
+fn contains(rect: Rect, column: u16, row: u16) -> bool {
+    rect.x <= column && column < rect.right() && rect.y <= row && row < rect.bottom()
+}
+
+/// NOTE clicks and scrolling hit what was drawn in the last frame
+fn handle_mouse(event: MouseEvent, app: &mut App) {
+    let (column, row) = (event.column, event.row);
+    if let Page::Help | Page::Log | Page::Stats = app.page_selected {
+        match event.kind {
+            MouseEventKind::ScrollDown => app.scroll_help_down(),
+            MouseEventKind::ScrollUp => app.scroll_help_up(),
+            _ => {}
+        }
+        return;
+    }
+    // NOTE keys typed so far are dropped, clicks do not take part in key sequences
+    app.pending_keys.clear();
+    app.pending_since = None;
+    app.count = None;
+    if app.mode == Mode::Command {
+        return;
+    }
+    let list = app.layout.task_list;
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            if contains(list, column, row) {
+                let idx = app.task_list.state.offset() + (row - list.y) as usize;
+                app.click_task(idx);
+            } else if contains(app.layout.clock, column, row) {
+                app.toggle_timer();
+            } else if contains(app.layout.other_tab, column, row) {
+                app.tab_toggle();
+            } else if let Some(idx) = app
+                .layout
+                .presets
+                .iter()
+                .position(|preset| contains(*preset, column, row))
+            {
+                app.click_preset(idx);
+            } else if let Some(idx) = app
+                .layout
+                .fields
+                .iter()
+                .position(|field| contains(*field, column, row))
+            {
+                app.click_field(idx);
+            }
+        }
+        MouseEventKind::ScrollDown if contains(list, column, row) => app.select_next_task(),
+        MouseEventKind::ScrollUp if contains(list, column, row) => app.select_prev_task(),
+        _ => {}
+    }
+}
+
+fn insert_key(app: &mut App, key: &KeyEvent) {
+    // NOTE these keys has no special mapping, fields decide which chars they take
+    let typed = !key
+        .modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+    if let (KeyCode::Char(code), true) = (key.code, typed) {
+        app.push_user_input_field(code);
+    }
 }
@@ -1,6 +1,118 @@
-use crate::app::{App, AppAction};
+use crate::app::{App, AppAction, Mode};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use std::collections::HashMap;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::read_to_string;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+pub type KeySequence = Vec<KeyEvent>;
+
+pub type Keymap = HashMap<KeySequence, AppAction>;
+
+pub const DEFAULT_LEADER: KeyEvent = KeyEvent::new(KeyCode::Char('\\'), KeyModifiers::NONE);
+
+/// NOTE how long a started key sequence waits for its next key
+pub const SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+// NOTE multi-key bindings, written the same way as in keybindings file,
+// `<leader>` stands for the leader key
+const NORMAL_SEQUENCES: [(&str, AppAction); 14] = [
+    ("g g", SELECT_FIRST_TASK),
+    ("c c", EDIT_TASK),
+    ("<leader> a", ARCHIVE_DONE_TASKS),
+    ("<leader> e", EDIT_NOTES_IN_EDITOR),
+    ("<leader> l", OPEN_LOG),
+    ("<leader> t", OPEN_STATS),
+    ("Ctrl-w w", TOGGLE_FOCUS),
+    ("Ctrl-w Ctrl-w", TOGGLE_FOCUS),
+    ("d d", DELETE_TASKS),
+    ("y y", YANK_TASKS),
+    ("Z Z", QUIT_APP),
+    ("<leader> s s", ABORT_TIMER),
+    ("<leader> p", PAUSE_TIMER),
+    ("<leader> o", LAUNCH_TIMER),
+];
+
+const INSERT_SEQUENCES: [(&str, AppAction); 1] = [("Ctrl-x Ctrl-e", EDIT_NOTES_IN_EDITOR)];
+
+const VISUAL_SEQUENCES: [(&str, AppAction); 1] = [("g g", SELECT_FIRST_TASK)];
+
+const HELP_PAGE_SEQUENCES: [(&str, AppAction); 1] = [("g g", SCROLL_HELP_TOP)];
+
+// NOTE readline style editing, shared by input fields in Insert mode and command line
+const LINE_EDITING_KEYBINDINGS: [(KeyEvent, AppAction); 18] = [
+    (
+        KeyEvent::new(KeyCode::Left, KeyModifiers::NONE),
+        CURSOR_LEFT,
+    ),
+    (
+        KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL),
+        CURSOR_LEFT,
+    ),
+    (
+        KeyEvent::new(KeyCode::Right, KeyModifiers::NONE),
+        CURSOR_RIGHT,
+    ),
+    (
+        KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL),
+        CURSOR_RIGHT,
+    ),
+    (
+        KeyEvent::new(KeyCode::Home, KeyModifiers::NONE),
+        CURSOR_HOME,
+    ),
+    (
+        KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL),
+        CURSOR_HOME,
+    ),
+    (KeyEvent::new(KeyCode::End, KeyModifiers::NONE), CURSOR_END),
+    (
+        KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL),
+        CURSOR_END,
+    ),
+    (
+        KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL),
+        CURSOR_WORD_LEFT,
+    ),
+    (
+        KeyEvent::new(KeyCode::Char('b'), KeyModifiers::ALT),
+        CURSOR_WORD_LEFT,
+    ),
+    (
+        KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL),
+        CURSOR_WORD_RIGHT,
+    ),
+    (
+        KeyEvent::new(KeyCode::Char('f'), KeyModifiers::ALT),
+        CURSOR_WORD_RIGHT,
+    ),
+    (
+        KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE),
+        DELETE_CHAR_FORWARD,
+    ),
+    (
+        KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+        DELETE_CHAR_FORWARD,
+    ),
+    (
+        KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL),
+        DELETE_WORD_BACK,
+    ),
+    (
+        KeyEvent::new(KeyCode::Backspace, KeyModifiers::ALT),
+        DELETE_WORD_BACK,
+    ),
+    (
+        KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL),
+        DELETE_TO_START,
+    ),
+    (
+        KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL),
+        DELETE_TO_END,
+    ),
+];
 
 // NOTE function pointer in Rust works this way
 // https://doc.rust-lang.org/std/primitive.fn.html#creating-function-pointers
@@ -26,28 +138,140 @@ const SET_TIMER: AppAction = App::set_timer;
 
 const ADD_TASK: AppAction = App::add_task;
 
-// TODO Task Manager Mode Keymap
 const DELETE_TASK: AppAction = App::delete_task;
 
 const OPEN_HELP: AppAction = App::open_help;
 
+const OPEN_LOG: AppAction = App::open_log;
+
+const OPEN_STATS: AppAction = App::open_stats;
+
+const SCROLL_HELP_DOWN: AppAction = App::scroll_help_down;
+
+const SCROLL_HELP_UP: AppAction = App::scroll_help_up;
+
+const SCROLL_HELP_PAGE_DOWN: AppAction = App::scroll_help_page_down;
+
+const SCROLL_HELP_PAGE_UP: AppAction = App::scroll_help_page_up;
+
+const SCROLL_HELP_TOP: AppAction = App::scroll_help_top;
+
+const SCROLL_HELP_BOTTOM: AppAction = App::scroll_help_bottom;
+
 const QUIT_APP: AppAction = App::quit;
 
+const CONFIRM_INPUT: AppAction = App::confirm_input;
+
+const SELECT_NEXT_TASK: AppAction = App::select_next_task;
+
+const SELECT_PREV_TASK: AppAction = App::select_prev_task;
+
+const ENTER_NORMAL_MODE: AppAction = App::enter_normal_mode;
+
+const ENTER_INSERT_MODE: AppAction = App::enter_insert_mode;
+
+const ENTER_VISUAL_MODE: AppAction = App::enter_visual_mode;
+
+const ENTER_COMMAND_MODE: AppAction = App::enter_command_mode;
+
+const EXECUTE_COMMAND: AppAction = App::execute_command;
+
+const COMPLETE_NEXT: AppAction = App::complete_next;
+
+const COMPLETE_PREV: AppAction = App::complete_prev;
+
+const DELETE_TASKS: AppAction = App::delete_tasks;
+
+const YANK_TASKS: AppAction = App::yank_tasks;
+
+const SELECT_FIRST_TASK: AppAction = App::select_first_task;
+
+const SELECT_TASK_PAGE_DOWN: AppAction = App::select_task_page_down;
+
+const SELECT_TASK_PAGE_UP: AppAction = App::select_task_page_up;
+
+const SELECT_NEXT: AppAction = App::select_next;
+
+const SELECT_PREV: AppAction = App::select_prev;
+
+const CONFIRM: AppAction = App::confirm;
+
+const ACTIVATE_TASK: AppAction = App::activate_task;
+
+const TOGGLE_FOCUS: AppAction = App::toggle_focus;
+
+const EDIT_TASK: AppAction = App::edit_task;
+
+const CANCEL_EDIT: AppAction = App::cancel_edit;
+
+const TOGGLE_TASK_DONE: AppAction = App::toggle_task_done;
+
+const ARCHIVE_DONE_TASKS: AppAction = App::archive_done_tasks;
+
+const MOVE_TASK_DOWN: AppAction = App::move_task_down;
+
+const MOVE_TASK_UP: AppAction = App::move_task_up;
+
+const UNDO: AppAction = App::undo;
+
+const SUSPEND: AppAction = App::suspend;
+
+const NEXT_PRESET: AppAction = App::next_preset;
+
+const PREV_PRESET: AppAction = App::prev_preset;
+
+const LOG_INTERNAL_INTERRUPTION: AppAction = App::log_internal_interruption;
+
+const LOG_EXTERNAL_INTERRUPTION: AppAction = App::log_external_interruption;
+
+const CURSOR_LEFT: AppAction = App::cursor_left;
+
+const CURSOR_RIGHT: AppAction = App::cursor_right;
+
+const CURSOR_HOME: AppAction = App::cursor_home;
+
+const CURSOR_END: AppAction = App::cursor_end;
+
+const CURSOR_WORD_LEFT: AppAction = App::cursor_word_left;
+
+const CURSOR_WORD_RIGHT: AppAction = App::cursor_word_right;
+
+const DELETE_CHAR_FORWARD: AppAction = App::delete_char_forward;
+
+const DELETE_WORD_BACK: AppAction = App::delete_word_back;
+
+const DELETE_TO_START: AppAction = App::delete_to_start;
+
+const DELETE_TO_END: AppAction = App::delete_to_end;
+
+const INSERT_NEWLINE: AppAction = App::insert_newline;
+
+const CURSOR_UP: AppAction = App::cursor_up;
+
+const CURSOR_DOWN: AppAction = App::cursor_down;
+
+const EDIT_NOTES_IN_EDITOR: AppAction = App::edit_notes_in_editor;
+
+const SELECT_LAST_TASK: AppAction = App::select_last_task;
+
+const PASTE_AFTER: AppAction = App::paste_after;
+
+const PASTE_BEFORE: AppAction = App::paste_before;
+
+const DELETE_SELECTED_TASKS: AppAction = App::delete_selected_tasks;
+
+const YANK_SELECTED_TASKS: AppAction = App::yank_selected_tasks;
+
 lazy_static! {
-    pub static ref TIMER_SETTING_KEYBINDINGS: HashMap<KeyEvent, AppAction> = {
+    pub static ref NORMAL_KEYBINDINGS: HashMap<KeyEvent, AppAction> = {
         let mut m = HashMap::new();
-    // clear input field
-        m.insert(
-            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
-            CLEAR_INPUT_FIELD,
-        );
     // switch to another tab
         m.insert(
-            KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE),
             TAB_TOGGLE,
         );
         m.insert(
-            KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE),
             TAB_TOGGLE,
         );
         m.insert(
@@ -58,48 +282,168 @@ lazy_static! {
             KeyEvent::new(KeyCode::Right, KeyModifiers::NONE),
             TAB_TOGGLE,
         );
-
-    // select next field
+    // select next task or field, depending on focus
         m.insert(
             KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE),
-            SELECT_NEXT_FIELD,
+            SELECT_NEXT,
         );
         m.insert(
-            KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
-            SELECT_NEXT_FIELD,
+            KeyEvent::new(KeyCode::Down, KeyModifiers::NONE),
+            SELECT_NEXT,
         );
+    // select previous task or field, depending on focus
         m.insert(
-            KeyEvent::new(KeyCode::Down, KeyModifiers::NONE),
-            SELECT_NEXT_FIELD,
+            KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE),
+            SELECT_PREV,
         );
-    // select previous field
         m.insert(
             KeyEvent::new(KeyCode::Up, KeyModifiers::NONE),
-            SELECT_PREV_FIELD,
+            SELECT_PREV,
         );
+    // move a page in task list
         m.insert(
-            KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE),
-            SELECT_PREV_FIELD,
+            KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE),
+            SELECT_TASK_PAGE_DOWN,
         );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL),
+            SELECT_TASK_PAGE_DOWN,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::PageUp, KeyModifiers::NONE),
+            SELECT_TASK_PAGE_UP,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL),
+            SELECT_TASK_PAGE_UP,
+        );
+    // select first task
+        m.insert(
+            KeyEvent::new(KeyCode::Home, KeyModifiers::NONE),
+            SELECT_FIRST_TASK,
+        );
+    // select next field
+        m.insert(
+            KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
+            SELECT_NEXT_FIELD,
+        );
+    // select previous field
         m.insert(
             KeyEvent::new(KeyCode::BackTab, KeyModifiers::NONE),
             SELECT_PREV_FIELD,
         );
-    // backspace erase one char
+    // edit input fields
         m.insert(
-            KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE),
-            POP_USER_INPUT_FIELD,
+            KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE),
+            ENTER_INSERT_MODE,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Insert, KeyModifiers::NONE),
+            ENTER_INSERT_MODE,
+        );
+    // select a range of tasks
+        m.insert(
+            KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE),
+            ENTER_VISUAL_MODE,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('V'), KeyModifiers::NONE),
+            ENTER_VISUAL_MODE,
+        );
+    // open command line
+        m.insert(
+            KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE),
+            ENTER_COMMAND_MODE,
+        );
+    // select last task
+        m.insert(
+            KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE),
+            SELECT_LAST_TASK,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::End, KeyModifiers::NONE),
+            SELECT_LAST_TASK,
+        );
+    // delete task
+        m.insert(
+            KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE),
+            DELETE_TASK,
+        );
+    // edit task
+        m.insert(
+            KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE),
+            EDIT_TASK,
+        );
+    // stop editing task
+        m.insert(
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            CANCEL_EDIT,
+        );
+    // undo task list changes
+        m.insert(
+            KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE),
+            UNDO,
+        );
+    // suspend to shell
+        m.insert(
+            KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL),
+            SUSPEND,
+        );
+    // switch timer preset
+        m.insert(
+            KeyEvent::new(KeyCode::Char(']'), KeyModifiers::NONE),
+            NEXT_PRESET,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('['), KeyModifiers::NONE),
+            PREV_PRESET,
+        );
+    // log an interruption of the pomodoro running
+        m.insert(
+            KeyEvent::new(KeyCode::Char('\''), KeyModifiers::NONE),
+            LOG_INTERNAL_INTERRUPTION,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('-'), KeyModifiers::NONE),
+            LOG_EXTERNAL_INTERRUPTION,
+        );
+    // mark task done
+        m.insert(
+            KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE),
+            TOGGLE_TASK_DONE,
+        );
+    // reorder tasks
+        m.insert(
+            KeyEvent::new(KeyCode::Char('J'), KeyModifiers::SHIFT),
+            MOVE_TASK_DOWN,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('K'), KeyModifiers::SHIFT),
+            MOVE_TASK_UP,
+        );
+    // paste yanked tasks
+        m.insert(
+            KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE),
+            PASTE_AFTER,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('P'), KeyModifiers::NONE),
+            PASTE_BEFORE,
         );
     // quit app
         m.insert(
             KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::NONE),
             QUIT_APP,
         );
-    // abort timer
         m.insert(
             KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE),
             QUIT_APP,
         );
+    // abort timer
+        m.insert(
+            KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE),
+            ABORT_TIMER,
+        );
     // toggle timer state
         m.insert(
             KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
@@ -107,7 +451,7 @@ lazy_static! {
         );
     // pause timer
         m.insert(
-            KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
             PAUSE_TIMER,
         );
     // launch timer
@@ -115,41 +459,52 @@ lazy_static! {
             KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE),
             LAUNCH_TIMER,
         );
-    // change pomodoro timer settings
-        m.insert(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), SET_TIMER);
+    // activate task or apply input fields, depending on focus
+        m.insert(
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+            CONFIRM,
+        );
     // open help
         m.insert(
             KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE),
             OPEN_HELP,
         );
+        m.insert(
+            KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE),
+            OPEN_HELP,
+        );
         m
     };
 
-    /// NOTE keybinding for TaskManager
-    pub static ref TASK_MANAGER_KEYBINDINGS: HashMap<KeyEvent, AppAction> = {
+    /// NOTE keybinding for Insert mode
+    pub static ref INSERT_KEYBINDINGS: HashMap<KeyEvent, AppAction> = {
         let mut m = HashMap::new();
-    // clear field
+    // suspend to shell
         m.insert(
-            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
-            CLEAR_INPUT_FIELD,
-    // switch tab
+            KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL),
+            SUSPEND,
         );
+    // stop editing task
         m.insert(
-            KeyEvent::new(KeyCode::Left, KeyModifiers::NONE),
-            TAB_TOGGLE,
+            KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            CANCEL_EDIT,
         );
+    // back to normal mode
         m.insert(
-            KeyEvent::new(KeyCode::Right, KeyModifiers::NONE),
-            TAB_TOGGLE,
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            ENTER_NORMAL_MODE,
         );
+    // move cursor and erase
+        m.extend(LINE_EDITING_KEYBINDINGS);
     // select next field
         m.insert(
             KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
             SELECT_NEXT_FIELD,
         );
+    // move between lines of notes, or fields
         m.insert(
             KeyEvent::new(KeyCode::Down, KeyModifiers::NONE),
-            SELECT_NEXT_FIELD,
+            CURSOR_DOWN,
         );
     // select prev field
         m.insert(
@@ -158,16 +513,208 @@ lazy_static! {
         );
         m.insert(
             KeyEvent::new(KeyCode::Up, KeyModifiers::NONE),
-            SELECT_PREV_FIELD,
+            CURSOR_UP,
+        );
+    // line break in notes
+        m.insert(
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT),
+            INSERT_NEWLINE,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL),
+            INSERT_NEWLINE,
+        );
+    // backspace erase one char
+        m.insert(
+            KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE),
+            POP_USER_INPUT_FIELD,
+        );
+    // change pomodoro timer settings or add task
+        m.insert(
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+            CONFIRM_INPUT,
+        );
+    // open help
+        m.insert(
+            KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE),
+            OPEN_HELP,
+        );
+        m
+    };
+
+    /// NOTE keybinding for Visual mode
+    pub static ref VISUAL_KEYBINDINGS: HashMap<KeyEvent, AppAction> = {
+        let mut m = HashMap::new();
+    // suspend to shell
+        m.insert(
+            KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL),
+            SUSPEND,
+        );
+    // back to normal mode
+        m.insert(
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            ENTER_NORMAL_MODE,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE),
+            ENTER_NORMAL_MODE,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('V'), KeyModifiers::NONE),
+            ENTER_NORMAL_MODE,
+        );
+    // extend selection
+        m.insert(
+            KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE),
+            SELECT_NEXT_TASK,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Down, KeyModifiers::NONE),
+            SELECT_NEXT_TASK,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE),
+            SELECT_PREV_TASK,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Up, KeyModifiers::NONE),
+            SELECT_PREV_TASK,
+        );
+    // delete selected tasks
+        m.insert(
+            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
+            DELETE_SELECTED_TASKS,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE),
+            DELETE_SELECTED_TASKS,
+        );
+    // yank selected tasks
+        m.insert(
+            KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE),
+            YANK_SELECTED_TASKS,
+        );
+    // toggle timer state
+        m.insert(
+            KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
+            TOGGLE_TIMER,
+        );
+    // open help
+        m.insert(
+            KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE),
+            OPEN_HELP,
+        );
+        m
+    };
+
+    /// NOTE keybinding for Command line
+    pub static ref COMMAND_KEYBINDINGS: HashMap<KeyEvent, AppAction> = {
+        let mut m = HashMap::new();
+    // back to normal mode
+        m.insert(
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            ENTER_NORMAL_MODE,
         );
+    // run command
+        m.insert(
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+            EXECUTE_COMMAND,
+        );
+    // cycle through completions
+        m.insert(
+            KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
+            COMPLETE_NEXT,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL),
+            COMPLETE_NEXT,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::BackTab, KeyModifiers::NONE),
+            COMPLETE_PREV,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+            COMPLETE_PREV,
+        );
+    // move cursor and erase
+        m.extend(LINE_EDITING_KEYBINDINGS);
         m.insert(
             KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE),
             POP_USER_INPUT_FIELD,
         );
-        m.insert(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), ADD_TASK);
         m
     };
-    // TODO generate Keybindings Help from this static Hashmap
+
+    /// NOTE keybinding for Help page
+    pub static ref HELP_PAGE_KEYBINDINGS: HashMap<KeyEvent, AppAction> = {
+        let mut m = HashMap::new();
+    // suspend to shell
+        m.insert(
+            KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL),
+            SUSPEND,
+        );
+    // scroll
+        m.insert(
+            KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE),
+            SCROLL_HELP_DOWN,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Down, KeyModifiers::NONE),
+            SCROLL_HELP_DOWN,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE),
+            SCROLL_HELP_UP,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Up, KeyModifiers::NONE),
+            SCROLL_HELP_UP,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE),
+            SCROLL_HELP_PAGE_DOWN,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+            SCROLL_HELP_PAGE_DOWN,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::PageUp, KeyModifiers::NONE),
+            SCROLL_HELP_PAGE_UP,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL),
+            SCROLL_HELP_PAGE_UP,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Home, KeyModifiers::NONE),
+            SCROLL_HELP_TOP,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE),
+            SCROLL_HELP_BOTTOM,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::End, KeyModifiers::NONE),
+            SCROLL_HELP_BOTTOM,
+        );
+    // leave help page
+        m.insert(
+            KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE),
+            QUIT_APP,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            QUIT_APP,
+        );
+        m.insert(
+            KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE),
+            QUIT_APP,
+        );
+        m
+    };
+
     pub static ref KEYBINDINGS_HELP_MESSAGE: HashMap<AppAction, &'static str> = {
         let mut m = HashMap::new();
         m.insert(TAB_TOGGLE, "Switch to next Tab");
@@ -195,6 +742,763 @@ lazy_static! {
         m.insert(DELETE_TASK, "Delete current selected task");
 
         m.insert(OPEN_HELP, "Open this help page");
+
+        m.insert(OPEN_LOG, "Open log page, it scrolls like help page");
+
+        m.insert(
+            OPEN_STATS,
+            "Open stats page, pomodoros and interruptions by period and task",
+        );
+
+        m.insert(QUIT_APP, "Quit the app or leave this page");
+
+        m.insert(CONFIRM_INPUT, "Change timer setting or add task, depending on the tab");
+
+        m.insert(SELECT_NEXT_TASK, "Select next task");
+
+        m.insert(SELECT_PREV_TASK, "Select previous task");
+
+        m.insert(ENTER_NORMAL_MODE, "Back to Normal mode");
+
+        m.insert(ENTER_INSERT_MODE, "Edit input fields in Insert mode");
+
+        m.insert(ENTER_VISUAL_MODE, "Select a range of tasks in Visual mode");
+
+        m.insert(ENTER_COMMAND_MODE, "Open command line");
+
+        m.insert(EXECUTE_COMMAND, "Run command");
+
+        m.insert(COMPLETE_NEXT, "Next completion");
+
+        m.insert(COMPLETE_PREV, "Previous completion");
+
+        m.insert(DELETE_TASKS, "Delete selected task, or as many as the count");
+
+        m.insert(YANK_TASKS, "Yank selected task, or as many as the count");
+
+        m.insert(SELECT_FIRST_TASK, "Select first task");
+
+        m.insert(SELECT_TASK_PAGE_DOWN, "Move one page down in task list");
+
+        m.insert(SELECT_TASK_PAGE_UP, "Move one page up in task list");
+
+        m.insert(SELECT_NEXT, "Select next task or input field, depending on focus");
+
+        m.insert(SELECT_PREV, "Select previous task or input field, depending on focus");
+
+        m.insert(CONFIRM, "Activate task or apply input fields, depending on focus");
+
+        m.insert(ACTIVATE_TASK, "Count next pomodoros against selected task");
+
+        m.insert(TOGGLE_FOCUS, "Move focus between task list and input fields");
+
+        m.insert(EDIT_TASK, "Edit selected task in task manager");
+
+        m.insert(CANCEL_EDIT, "Stop editing task, dropping changes");
+
+        m.insert(TOGGLE_TASK_DONE, "Mark selected task done or not done");
+
+        m.insert(ARCHIVE_DONE_TASKS, "Move done tasks out of task list");
+
+        m.insert(MOVE_TASK_DOWN, "Move selected task down");
+
+        m.insert(MOVE_TASK_UP, "Move selected task up");
+
+        m.insert(UNDO, "Undo last change to task list");
+
+        m.insert(SUSPEND, "Suspend to shell, `fg` comes back");
+
+        m.insert(NEXT_PRESET, "Switch to next timer preset");
+
+        m.insert(PREV_PRESET, "Switch to previous timer preset");
+
+        m.insert(
+            LOG_INTERNAL_INTERRUPTION,
+            "Log an internal interruption, with a note if typed",
+        );
+
+        m.insert(
+            LOG_EXTERNAL_INTERRUPTION,
+            "Log an external interruption, with a note if typed",
+        );
+
+        m.insert(CURSOR_LEFT, "Move cursor one character left");
+
+        m.insert(CURSOR_RIGHT, "Move cursor one character right");
+
+        m.insert(CURSOR_HOME, "Move cursor to start of line");
+
+        m.insert(CURSOR_END, "Move cursor to end of line");
+
+        m.insert(CURSOR_WORD_LEFT, "Move cursor one word left");
+
+        m.insert(CURSOR_WORD_RIGHT, "Move cursor one word right");
+
+        m.insert(DELETE_CHAR_FORWARD, "Erase character under cursor");
+
+        m.insert(DELETE_WORD_BACK, "Erase word before cursor");
+
+        m.insert(DELETE_TO_START, "Erase from start of line to cursor");
+
+        m.insert(DELETE_TO_END, "Erase from cursor to end of line");
+
+        m.insert(INSERT_NEWLINE, "Start a new line in notes");
+
+        m.insert(CURSOR_UP, "Move to line above in notes, or previous field");
+
+        m.insert(CURSOR_DOWN, "Move to line below in notes, or next field");
+
+        m.insert(EDIT_NOTES_IN_EDITOR, "Edit notes in $EDITOR");
+
+        m.insert(SELECT_LAST_TASK, "Select last task");
+
+        m.insert(PASTE_AFTER, "Paste yanked tasks after selected task");
+
+        m.insert(PASTE_BEFORE, "Paste yanked tasks before selected task");
+
+        m.insert(DELETE_SELECTED_TASKS, "Delete selected tasks");
+
+        m.insert(YANK_SELECTED_TASKS, "Yank selected tasks");
+
+        m.insert(SCROLL_HELP_DOWN, "Scroll down one line");
+
+        m.insert(SCROLL_HELP_UP, "Scroll up one line");
+
+        m.insert(SCROLL_HELP_PAGE_DOWN, "Scroll down one page");
+
+        m.insert(SCROLL_HELP_PAGE_UP, "Scroll up one page");
+
+        m.insert(SCROLL_HELP_TOP, "Scroll to the top");
+
+        m.insert(SCROLL_HELP_BOTTOM, "Scroll to the bottom");
         m
     };
+
+    /// NOTE names used for actions in keybindings file
+    pub static ref ACTION_NAMES: HashMap<&'static str, AppAction> = {
+        let mut m = HashMap::new();
+        m.insert("tab_toggle", TAB_TOGGLE);
+        m.insert("clear_input_field", CLEAR_INPUT_FIELD);
+        m.insert("select_next_field", SELECT_NEXT_FIELD);
+        m.insert("select_prev_field", SELECT_PREV_FIELD);
+        m.insert("delete_char", POP_USER_INPUT_FIELD);
+        m.insert("abort_timer", ABORT_TIMER);
+        m.insert("toggle_timer", TOGGLE_TIMER);
+        m.insert("pause_timer", PAUSE_TIMER);
+        m.insert("launch_timer", LAUNCH_TIMER);
+        m.insert("set_timer", SET_TIMER);
+        m.insert("add_task", ADD_TASK);
+        m.insert("delete_task", DELETE_TASK);
+        m.insert("open_help", OPEN_HELP);
+        m.insert("open_log", OPEN_LOG);
+        m.insert("open_stats", OPEN_STATS);
+        m.insert("quit", QUIT_APP);
+        m.insert("scroll_down", SCROLL_HELP_DOWN);
+        m.insert("scroll_up", SCROLL_HELP_UP);
+        m.insert("scroll_page_down", SCROLL_HELP_PAGE_DOWN);
+        m.insert("scroll_page_up", SCROLL_HELP_PAGE_UP);
+        m.insert("scroll_top", SCROLL_HELP_TOP);
+        m.insert("scroll_bottom", SCROLL_HELP_BOTTOM);
+        m.insert("confirm_input", CONFIRM_INPUT);
+        m.insert("select_next_task", SELECT_NEXT_TASK);
+        m.insert("select_prev_task", SELECT_PREV_TASK);
+        m.insert("normal_mode", ENTER_NORMAL_MODE);
+        m.insert("insert_mode", ENTER_INSERT_MODE);
+        m.insert("visual_mode", ENTER_VISUAL_MODE);
+        m.insert("command_mode", ENTER_COMMAND_MODE);
+        m.insert("execute_command", EXECUTE_COMMAND);
+        m.insert("complete_next", COMPLETE_NEXT);
+        m.insert("complete_prev", COMPLETE_PREV);
+        m.insert("delete_tasks", DELETE_TASKS);
+        m.insert("yank_tasks", YANK_TASKS);
+        m.insert("select_first_task", SELECT_FIRST_TASK);
+        m.insert("task_page_down", SELECT_TASK_PAGE_DOWN);
+        m.insert("task_page_up", SELECT_TASK_PAGE_UP);
+        m.insert("select_next", SELECT_NEXT);
+        m.insert("select_prev", SELECT_PREV);
+        m.insert("confirm", CONFIRM);
+        m.insert("activate_task", ACTIVATE_TASK);
+        m.insert("toggle_focus", TOGGLE_FOCUS);
+        m.insert("edit_task", EDIT_TASK);
+        m.insert("cancel_edit", CANCEL_EDIT);
+        m.insert("toggle_task_done", TOGGLE_TASK_DONE);
+        m.insert("archive_done_tasks", ARCHIVE_DONE_TASKS);
+        m.insert("move_task_down", MOVE_TASK_DOWN);
+        m.insert("move_task_up", MOVE_TASK_UP);
+        m.insert("undo", UNDO);
+        m.insert("suspend", SUSPEND);
+        m.insert("next_preset", NEXT_PRESET);
+        m.insert("prev_preset", PREV_PRESET);
+        m.insert("internal_interruption", LOG_INTERNAL_INTERRUPTION);
+        m.insert("external_interruption", LOG_EXTERNAL_INTERRUPTION);
+        m.insert("cursor_left", CURSOR_LEFT);
+        m.insert("cursor_right", CURSOR_RIGHT);
+        m.insert("cursor_home", CURSOR_HOME);
+        m.insert("cursor_end", CURSOR_END);
+        m.insert("cursor_word_left", CURSOR_WORD_LEFT);
+        m.insert("cursor_word_right", CURSOR_WORD_RIGHT);
+        m.insert("delete_char_forward", DELETE_CHAR_FORWARD);
+        m.insert("delete_word_back", DELETE_WORD_BACK);
+        m.insert("delete_to_start", DELETE_TO_START);
+        m.insert("delete_to_end", DELETE_TO_END);
+        m.insert("insert_newline", INSERT_NEWLINE);
+        m.insert("cursor_up", CURSOR_UP);
+        m.insert("cursor_down", CURSOR_DOWN);
+        m.insert("edit_notes_in_editor", EDIT_NOTES_IN_EDITOR);
+        m.insert("select_last_task", SELECT_LAST_TASK);
+        m.insert("paste_after", PASTE_AFTER);
+        m.insert("paste_before", PASTE_BEFORE);
+        m.insert("delete_selected_tasks", DELETE_SELECTED_TASKS);
+        m.insert("yank_selected_tasks", YANK_SELECTED_TASKS);
+        m
+    };
+
+    static ref ACTION_NAMES_REVERSE: HashMap<AppAction, &'static str> =
+        ACTION_NAMES.iter().map(|(name, action)| (*action, *name)).collect();
+}
+
+/// Readable name of a key press, e.g. `Ctrl-d`, `Space`, `BackTab`
+pub fn key_name(key: &KeyEvent) -> String {
+    let code = match key.code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "BackTab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Delete => "Delete".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        KeyCode::Insert => "Insert".to_string(),
+        other => format!("{:?}", other),
+    };
+    let mut name = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        name.push_str("Ctrl-");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        name.push_str("Alt-");
+    }
+    // NOTE shift is already part of the char itself
+    if key.modifiers.contains(KeyModifiers::SHIFT) && !matches!(key.code, KeyCode::Char(_)) {
+        name.push_str("Shift-");
+    }
+    name.push_str(&code);
+    name
+}
+
+/// Readable name of a key sequence, keys separated by spaces, e.g. `g g`
+pub fn sequence_name(keys: &[KeyEvent]) -> String {
+    keys.iter().map(key_name).collect::<Vec<_>>().join(" ")
+}
+
+/// NOTE keys of a keymap grouped by the action they trigger,
+/// as (keys, action name, help message)
+pub fn key_hints(keymap: &Keymap) -> Vec<(String, &'static str, &'static str)> {
+    let mut hints: BTreeMap<&'static str, (&'static str, Vec<String>)> = BTreeMap::new();
+    for (keys, action) in keymap.iter() {
+        if let (Some(name), Some(help_msg)) =
+            (action_name(action), KEYBINDINGS_HELP_MESSAGE.get(action))
+        {
+            hints
+                .entry(name)
+                .or_insert_with(|| (help_msg, Vec::new()))
+                .1
+                .push(sequence_name(keys));
+        }
+    }
+    hints
+        .into_iter()
+        .map(|(name, (help_msg, mut keys))| {
+            keys.sort();
+            (keys.join("/"), name, help_msg)
+        })
+        .collect()
+}
+
+/// NOTE help messages of actions that no keymap context binds to any key
+pub fn unbound_actions(keymaps: &Keymaps) -> Vec<&'static str> {
+    let bound: Vec<&'static str> = keymaps
+        .contexts()
+        .iter()
+        .flat_map(|(_, _, keymap)| keymap.values())
+        .filter_map(|action| KEYBINDINGS_HELP_MESSAGE.get(action).copied())
+        .collect();
+    let mut unbound: Vec<&'static str> = KEYBINDINGS_HELP_MESSAGE
+        .values()
+        .filter(|help_msg| !bound.contains(help_msg))
+        .copied()
+        .collect();
+    unbound.sort();
+    unbound
+}
+
+pub fn action_name(action: &AppAction) -> Option<&'static str> {
+    ACTION_NAMES_REVERSE.get(action).copied()
+}
+
+/// Parse a readable key spec back into a key press, the inverse of [`key_name`]
+///
+/// Modifiers are written as `Ctrl-`, `Alt-` or `Shift-` (also `C-`, `A-`/`M-` and `S-`),
+/// followed by a single char or a key name like `Space`, `Enter` or `F1`, case insensitive.
+pub fn parse_key(spec: &str) -> Result<KeyEvent, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = spec;
+    // NOTE a lone `-` is the minus key, not a modifier separator
+    while let Some((modifier, tail)) = rest.split_once('-').filter(|(_, tail)| !tail.is_empty()) {
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "c" => KeyModifiers::CONTROL,
+            "alt" | "a" | "m" => KeyModifiers::ALT,
+            "shift" | "s" => KeyModifiers::SHIFT,
+            _ => return Err(format!("unknown modifier `{}` in key `{}`", modifier, spec)),
+        };
+        rest = tail;
+    }
+
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match rest.to_ascii_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                _ => return Err(format!("unknown key `{}`", spec)),
+            },
+        },
+    };
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+/// Parse a key sequence, key specs separated by spaces, `<leader>` is the leader key
+pub fn parse_sequence(spec: &str, leader: KeyEvent) -> Result<KeySequence, String> {
+    let keys: KeySequence = spec
+        .split_whitespace()
+        .map(|key| match key.to_ascii_lowercase().as_str() {
+            "<leader>" => Ok(leader),
+            _ => parse_key(key),
+        })
+        .collect::<Result<_, _>>()?;
+    if keys.is_empty() {
+        return Err("empty key sequence".to_string());
+    }
+    Ok(keys)
+}
+
+/// NOTE single key bindings and sequences in one keymap
+fn build_keymap(
+    keys: &HashMap<KeyEvent, AppAction>,
+    sequences: &[(&str, AppAction)],
+    leader: KeyEvent,
+) -> Keymap {
+    let mut keymap: Keymap = keys
+        .iter()
+        .map(|(key, action)| (vec![*key], *action))
+        .collect();
+    for (spec, action) in sequences.iter() {
+        match parse_sequence(spec, leader) {
+            Ok(keys) => {
+                keymap.insert(keys, *action);
+            }
+            Err(e) => unreachable!("invalid default key sequence `{}`: {}", spec, e),
+        }
+    }
+    keymap
+}
+
+/// NOTE keybindings file, an optional leader key and one table per keymap context
+/// mapping action names to keys or key sequences, e.g.
+///
+/// ```toml
+/// leader = ","
+///
+/// [normal]
+/// delete_task = ["Ctrl-x", "<leader> d"]
+/// toggle_timer = "t"
+/// ```
+///
+/// keys listed for an action replace its default keys in that context
+#[derive(Deserialize)]
+struct KeybindingsFile {
+    leader: Option<String>,
+    #[serde(flatten)]
+    contexts: BTreeMap<String, BTreeMap<String, KeySpecs>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeySpecs {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpecs {
+    fn specs(&self) -> &[String] {
+        match self {
+            KeySpecs::One(spec) => std::slice::from_ref(spec),
+            KeySpecs::Many(specs) => specs,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymaps {
+    pub leader: KeyEvent,
+    pub normal: Keymap,
+    pub insert: Keymap,
+    pub visual: Keymap,
+    pub command: Keymap,
+    pub help_page: Keymap,
+}
+
+impl Default for Keymaps {
+    fn default() -> Self {
+        Self::with_leader(DEFAULT_LEADER)
+    }
+}
+
+impl Keymaps {
+    pub fn with_leader(leader: KeyEvent) -> Self {
+        Self {
+            leader,
+            normal: build_keymap(&NORMAL_KEYBINDINGS, &NORMAL_SEQUENCES, leader),
+            insert: build_keymap(&INSERT_KEYBINDINGS, &INSERT_SEQUENCES, leader),
+            visual: build_keymap(&VISUAL_KEYBINDINGS, &VISUAL_SEQUENCES, leader),
+            command: build_keymap(&COMMAND_KEYBINDINGS, &[], leader),
+            help_page: build_keymap(&HELP_PAGE_KEYBINDINGS, &HELP_PAGE_SEQUENCES, leader),
+        }
+    }
+
+    /// NOTE every keymap context as (name in keybindings file, title in help page, keymap),
+    /// help page is generated from here so it always matches the bindings
+    pub fn contexts(&self) -> [(&'static str, &'static str, &Keymap); 5] {
+        [
+            ("normal", "Normal Mode", &self.normal),
+            ("insert", "Insert Mode", &self.insert),
+            ("visual", "Visual Mode", &self.visual),
+            ("command", "Command Line", &self.command),
+            ("help_page", "Help, Log And Stats Page", &self.help_page),
+        ]
+    }
+
+    fn context_mut(&mut self, name: &str) -> Option<&mut Keymap> {
+        match name {
+            "normal" => Some(&mut self.normal),
+            "insert" => Some(&mut self.insert),
+            "visual" => Some(&mut self.visual),
+            "command" => Some(&mut self.command),
+            "help_page" => Some(&mut self.help_page),
+            _ => None,
+        }
+    }
+
+    pub fn for_mode(&self, mode: Mode) -> &Keymap {
+        match mode {
+            Mode::Normal => &self.normal,
+            Mode::Insert => &self.insert,
+            Mode::Visual => &self.visual,
+            Mode::Command => &self.command,
+        }
+    }
+
+    /// NOTE default keymaps with the keybindings file applied on top,
+    /// along with every problem found in the file.
+    /// A missing file is not a problem, an unreadable one falls back to defaults
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        match read_to_string(path) {
+            Ok(text) => Self::from_config(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (Self::default(), Vec::new()),
+            Err(e) => (
+                Self::default(),
+                vec![format!("failed to read {}: {}", path.display(), e)],
+            ),
+        }
+    }
+
+    pub fn from_config(text: &str) -> (Self, Vec<String>) {
+        let file: KeybindingsFile = match toml::from_str(text) {
+            Ok(file) => file,
+            Err(e) => {
+                return (
+                    Self::default(),
+                    vec![format!("invalid keybindings file: {}", e)],
+                )
+            }
+        };
+
+        let mut problems = Vec::new();
+        let leader = match file.leader.as_deref().map(parse_key) {
+            Some(Ok(leader)) => leader,
+            Some(Err(e)) => {
+                problems.push(format!("leader: {}", e));
+                DEFAULT_LEADER
+            }
+            None => DEFAULT_LEADER,
+        };
+        let mut keymaps = Self::with_leader(leader);
+        for (context, bindings) in file.contexts.iter() {
+            let Some(keymap) = keymaps.context_mut(context) else {
+                problems.push(format!("unknown keymap context [{}]", context));
+                continue;
+            };
+
+            // NOTE validate everything first, a broken entry keeps the default keys of its action
+            let mut remapped: Vec<(&str, AppAction, Vec<KeySequence>)> = Vec::new();
+            for (name, specs) in bindings.iter() {
+                let Some(action) = ACTION_NAMES.get(name.as_str()) else {
+                    problems.push(format!("[{}] unknown action `{}`", context, name));
+                    continue;
+                };
+                let keys: Result<Vec<KeySequence>, String> = specs
+                    .specs()
+                    .iter()
+                    .map(|spec| parse_sequence(spec, leader))
+                    .collect();
+                match keys {
+                    Ok(keys) => remapped.push((name, *action, keys)),
+                    Err(e) => problems.push(format!("[{}] {}: {}", context, name, e)),
+                }
+            }
+
+            // NOTE drop default keys of remapped actions before binding any new key,
+            // so swapping keys between two actions is not a conflict
+            keymap.retain(|_, action| {
+                !remapped
+                    .iter()
+                    .any(|(name, _, _)| action_name(action) == Some(name))
+            });
+            let mut bound_by: HashMap<KeySequence, &str> = HashMap::new();
+            for (name, action, keys) in remapped {
+                for sequence in keys {
+                    if let Some(other) = bound_by.get(&sequence) {
+                        problems.push(format!(
+                            "[{}] `{}` is bound to both `{}` and `{}`, keeping `{}`",
+                            context,
+                            sequence_name(&sequence),
+                            other,
+                            name,
+                            other
+                        ));
+                        continue;
+                    }
+                    if let Some(other) = keymap.get(&sequence).and_then(action_name) {
+                        problems.push(format!(
+                            "[{}] `{}` overrides the default binding for `{}`",
+                            context,
+                            sequence_name(&sequence),
+                            other
+                        ));
+                    }
+                    bound_by.insert(sequence.clone(), name);
+                    keymap.insert(sequence, action);
+                }
+            }
+        }
+        (keymaps, problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    /// NOTE name of the action `spec` is bound to in `keymap`
+    fn bound(keymap: &Keymap, spec: &str, leader: KeyEvent) -> Option<&'static str> {
+        keymap
+            .get(&parse_sequence(spec, leader).unwrap())
+            .and_then(action_name)
+    }
+
+    #[test]
+    fn parse_key_reads_modifiers_and_names() {
+        assert_eq!(
+            parse_key("Ctrl-d"),
+            Ok(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key("c-a-Enter"),
+            Ok(KeyEvent::new(
+                KeyCode::Enter,
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ))
+        );
+        assert_eq!(parse_key("space"), Ok(key(' ')));
+        assert_eq!(
+            parse_key("F12"),
+            Ok(KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE))
+        );
+        assert_eq!(parse_key("-"), Ok(key('-')));
+        assert_eq!(
+            parse_key("Ctrl--"),
+            Ok(KeyEvent::new(KeyCode::Char('-'), KeyModifiers::CONTROL))
+        );
+    }
+
+    #[test]
+    fn parse_key_rejects_unknown_keys() {
+        assert_eq!(
+            parse_key("Hyper-x"),
+            Err("unknown modifier `Hyper` in key `Hyper-x`".to_string())
+        );
+        assert_eq!(parse_key("F13"), Err("unknown key `F13`".to_string()));
+        assert_eq!(parse_key("foo"), Err("unknown key `foo`".to_string()));
+        assert!(parse_key("").is_err());
+    }
+
+    #[test]
+    fn parse_key_reads_key_names_back() {
+        for spec in ["Ctrl-d", "Alt-Enter", "Space", "Shift-Tab", "F1", "G"] {
+            assert_eq!(key_name(&parse_key(spec).unwrap()), spec);
+        }
+    }
+
+    #[test]
+    fn parse_sequence_reads_leader_and_keys() {
+        let leader = key(',');
+        assert_eq!(parse_sequence("g g", leader), Ok(vec![key('g'), key('g')]));
+        assert_eq!(
+            parse_sequence("<Leader>  s Ctrl-w", leader),
+            Ok(vec![
+                leader,
+                key('s'),
+                KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL)
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_sequence_rejects_empty_and_unknown_keys() {
+        assert_eq!(
+            parse_sequence("  ", DEFAULT_LEADER),
+            Err("empty key sequence".to_string())
+        );
+        assert_eq!(
+            parse_sequence("g foo", DEFAULT_LEADER),
+            Err("unknown key `foo`".to_string())
+        );
+    }
+
+    #[test]
+    fn default_sequences_follow_the_leader() {
+        let (keymaps, problems) = Keymaps::from_config("leader = \",\"\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(bound(&keymaps.normal, ", l", key(',')), Some("open_log"));
+        assert_eq!(bound(&keymaps.normal, "\\ l", key(',')), None);
+    }
+
+    #[test]
+    fn config_binds_sequences_and_rejects_a_bad_leader() {
+        let text = "leader = \"Hyper-x\"\n\n[normal]\ndelete_task = [\"<leader> d\", \"g x\"]\n";
+        let (keymaps, problems) = Keymaps::from_config(text);
+        assert_eq!(
+            problems,
+            vec!["leader: unknown modifier `Hyper` in key `Hyper-x`".to_string()]
+        );
+        assert_eq!(
+            bound(&keymaps.normal, "\\ d", DEFAULT_LEADER),
+            Some("delete_task")
+        );
+        assert_eq!(
+            bound(&keymaps.normal, "g x", DEFAULT_LEADER),
+            Some("delete_task")
+        );
+        assert_eq!(bound(&keymaps.normal, "x", DEFAULT_LEADER), None);
+    }
+
+    #[test]
+    fn config_replaces_default_keys_of_remapped_actions() {
+        let (keymaps, problems) = Keymaps::from_config("[normal]\ndelete_task = \"X\"\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(
+            bound(&keymaps.normal, "X", DEFAULT_LEADER),
+            Some("delete_task")
+        );
+        assert_eq!(bound(&keymaps.normal, "x", DEFAULT_LEADER), None);
+    }
+
+    #[test]
+    fn config_swaps_keys_without_conflict() {
+        let text = "[normal]\ndelete_task = \"e\"\nedit_task = \"x\"\n";
+        let (keymaps, problems) = Keymaps::from_config(text);
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(
+            bound(&keymaps.normal, "e", DEFAULT_LEADER),
+            Some("delete_task")
+        );
+        assert_eq!(
+            bound(&keymaps.normal, "x", DEFAULT_LEADER),
+            Some("edit_task")
+        );
+    }
+
+    #[test]
+    fn config_problems_keep_defaults() {
+        let text = "[normal]\nno_such_action = \"q\"\ndelete_task = \"Hyper-x\"\n\n[nowhere]\nquit = \"q\"\n";
+        let (keymaps, problems) = Keymaps::from_config(text);
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert!(problems.contains(&"unknown keymap context [nowhere]".to_string()));
+        assert!(problems.contains(&"[normal] unknown action `no_such_action`".to_string()));
+        assert_eq!(
+            bound(&keymaps.normal, "x", DEFAULT_LEADER),
+            Some("delete_task")
+        );
+    }
+
+    #[test]
+    fn config_reports_keys_bound_twice() {
+        let text = "[normal]\ndelete_task = \"X\"\nedit_task = \"X\"\n";
+        let (keymaps, problems) = Keymaps::from_config(text);
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert_eq!(
+            bound(&keymaps.normal, "X", DEFAULT_LEADER),
+            Some("delete_task")
+        );
+    }
+
+    #[test]
+    fn config_that_does_not_parse_gives_defaults() {
+        let (keymaps, problems) = Keymaps::from_config("[normal\n");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("invalid keybindings file"));
+        assert_eq!(
+            bound(&keymaps.normal, "x", DEFAULT_LEADER),
+            Some("delete_task")
+        );
+    }
+
+    #[test]
+    fn config_binds_the_command_context() {
+        let (keymaps, problems) = Keymaps::from_config("[command]\nquit = \"Ctrl-q\"\n");
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(
+            bound(&keymaps.command, "Ctrl-q", DEFAULT_LEADER),
+            Some("quit")
+        );
+    }
 }
@@ -0,0 +1,176 @@
+use crate::config;
+use crate::event::Event;
+use crate::sync::lock;
+use chrono::{DateTime, Local};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+
+/// NOTE read when `--log-level` is not given, e.g. `POMODORO_LOG=debug`
+pub const LOG_LEVEL_VAR: &str = "POMODORO_LOG";
+pub const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Info;
+
+const LOG_FILE: &str = "pomodoro.log";
+// NOTE past this the log file is rotated to `pomodoro.log.1`, older ones shift up
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+const KEPT_LOGS: usize = 3;
+// NOTE entries kept in memory for log page
+const RECENT_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl LogEntry {
+    fn line(&self) -> String {
+        format!(
+            "{} {:<5} {}: {}\n",
+            self.time.format("%Y-%m-%dT%H:%M:%S%.3f"),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: &Path) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+        })
+    }
+
+    fn write(&mut self, line: &str) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > MAX_LOG_SIZE {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// NOTE `pomodoro.log.2` becomes `pomodoro.log.3` and so on, the oldest one is dropped
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |n: usize| PathBuf::from(format!("{}.{}", self.path.display(), n));
+        for n in (1..KEPT_LOGS).rev() {
+            if rotated(n).exists() {
+                fs::rename(rotated(n), rotated(n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated(1))?;
+        *self = Self::open(&self.path)?;
+        Ok(())
+    }
+}
+
+struct Logger {
+    file: Mutex<Option<LogFile>>,
+    recent: Mutex<VecDeque<LogEntry>>,
+    // NOTE wakes main loop up for every entry, set once the event channel exists
+    sender: Mutex<Option<Sender<Event>>>,
+}
+
+static LOGGER: Logger = Logger {
+    file: Mutex::new(None),
+    recent: Mutex::new(VecDeque::new()),
+    sender: Mutex::new(None),
+};
+
+impl Log for Logger {
+    // NOTE other crates only get in with warnings and errors, they are too chatty otherwise
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+            && (metadata.level() <= Level::Warn
+                || metadata.target().starts_with(env!("CARGO_PKG_NAME")))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let entry = LogEntry {
+            time: Local::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+        if let Some(file) = lock(&self.file).as_mut() {
+            // NOTE nowhere left to report it to, log page still has the entry
+            let _ = file.write(&entry.line());
+        }
+        {
+            let mut recent = lock(&self.recent);
+            if recent.len() == RECENT_LIMIT {
+                recent.pop_front();
+            }
+            recent.push_back(entry.clone());
+        }
+        if let Some(sender) = lock(&self.sender).as_ref() {
+            let _ = sender.send(Event::Log(entry));
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = lock(&self.file).as_mut() {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+/// NOTE `$XDG_STATE_HOME/pomodoro/pomodoro.log`
+fn log_path() -> Option<PathBuf> {
+    config::state_dir().map(|dir| dir.join(LOG_FILE))
+}
+
+/// NOTE entries are kept for log page even when the log file can not be opened,
+/// which is returned as an error
+pub fn init(level: LevelFilter) -> Result<(), String> {
+    log::set_max_level(level);
+    log::set_logger(&LOGGER).map_err(|e| e.to_string())?;
+    let path = log_path().ok_or("no home directory for log file")?;
+    let file = LogFile::open(&path)
+        .map_err(|e| format!("failed to open log file {}: {}", path.display(), e))?;
+    *lock(&LOGGER.file) = Some(file);
+    Ok(())
+}
+
+pub fn set_sender(sender: Sender<Event>) {
+    *lock(&LOGGER.sender) = Some(sender);
+}
+
+/// NOTE None when log file could not be opened
+pub fn log_file_path() -> Option<PathBuf> {
+    lock(&LOGGER.file).as_ref().map(|file| file.path.clone())
+}
+
+/// NOTE oldest first
+pub fn recent_entries() -> Vec<LogEntry> {
+    lock(&LOGGER.recent).iter().cloned().collect()
+}
+
+/// NOTE `error`, `warn`, `info`, `debug`, `trace` or `off`
+pub fn parse_level(text: &str) -> Result<LevelFilter, String> {
+    text.parse()
+        .map_err(|_| format!("unknown log level `{}`", text))
+}
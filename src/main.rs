@@ -1,100 +1,543 @@
-use std::{
-    io::{self, stdout},
-    thread::sleep,
-    time::Duration,
-};
+use std::io::{self, stdout};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::thread;
 
 #[macro_use]
 extern crate lazy_static;
 
 use crossterm::{
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use ratatui::{prelude::*, widgets::*};
+use signal_hook::consts::signal::{SIGCONT, SIGSTOP, SIGTSTP};
+use unicode_width::UnicodeWidthStr;
 
 mod digits_clock;
 use crate::digits_clock::*;
 mod app;
 use crate::app::*;
 mod input;
-use crate::input::handle_events;
+use crate::input::{handle_event, handle_sequence_timeout, sequence_time_left};
 mod keybindings;
-use keybindings::{KEYBINDINGS_HELP_MESSAGE, TASK_MANAGER_KEYBINDINGS, TIMER_SETTING_KEYBINDINGS};
+use keybindings::{key_hints, key_name, sequence_name, unbound_actions, Keymaps};
+mod backup;
+mod cli;
+mod command;
+mod config;
+mod crash;
 mod custom_widgets;
-
-const FPS: u64 = 30;
+mod editor;
+mod event;
+use crate::event::{Event, Events};
+mod form;
+use crate::form::{Editor, Field, Form};
+mod history;
+mod hooks;
+mod logging;
+use crate::logging::LogEntry;
+mod mpris;
+mod presets;
+mod service;
+use crate::service::Service;
+mod storage;
+mod sync;
 
 fn main() -> io::Result<()> {
-    let mut args = std::env::args();
-    let _program = args.next().unwrap();
-    let fps = match args.next() {
-        Some(fps) => {
-            if let Ok(fps) = fps.parse::<u64>() {
-                fps
-            } else {
-                FPS
-            }
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
         }
-        None => FPS,
     };
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-    let interval: u64 = 1000 / fps;
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if args.version {
+        println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+    let log_level = match args.log_level() {
+        Ok(level) => level,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    let log_problem = logging::init(log_level).err();
 
+    install_panic_hook();
+    let events = Events::new()?;
+    logging::set_sender(events.sender());
+    if let Some(problem) = log_problem {
+        log::error!("{}", problem);
+    }
+    log::info!(
+        "{} {} started",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
     let mut app = App::default();
 
+    let (keymaps, problems) = match config::keybindings_path() {
+        Some(path) => Keymaps::load(&path),
+        None => (Keymaps::default(), Vec::new()),
+    };
+    if !problems.is_empty() {
+        // NOTE printed before entering alternate screen, so they are still there after quitting
+        for problem in problems.iter() {
+            eprintln!("keybindings: {}", problem);
+            log::warn!("keybindings: {}", problem);
+        }
+        app.open_help();
+    }
+    app.keybinding_problems = problems;
+    let data_path = load_data(&mut app);
+    load_presets(&mut app);
+    load_hooks(&mut app);
+    load_mpris(&mut app);
+    if let Some(name) = args.preset.as_deref() {
+        if let Err(e) = app.apply_preset(name) {
+            let names: Vec<&str> = app.presets.iter().map(|p| p.name.as_str()).collect();
+            eprintln!("{}, presets are: {}", e, names.join(", "));
+            std::process::exit(2);
+        }
+    }
+    let service = Service::start(events.sender());
+
+    resume_terminal()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        run(&mut terminal, &mut app, &keymaps, &events, &service)
+    }));
+    // NOTE saved after a crash too, tasks are most likely still fine
+    let saved = data_path.map(|path| (storage::save(&path, &app.saved_data()), path));
+    let restored = suspend_terminal();
+    if let Some((Err(e), path)) = saved {
+        log::error!("failed to save {}: {}", path.display(), e);
+        eprintln!("failed to save {}: {}", path.display(), e);
+    }
+    log::info!("quit");
+    match result {
+        Ok(result) => restored.and(result),
+        Err(payload) => panic::resume_unwind(payload),
+    }
+}
+
+fn load_presets(app: &mut App) {
+    let (presets, problems) = presets::load(presets::presets_path().as_deref());
+    for problem in problems.iter() {
+        log::warn!("presets: {}", problem);
+    }
+    app.set_presets(presets);
+}
+
+/// NOTE a broken hooks file is reported and no hooks run
+fn load_hooks(app: &mut App) {
+    let Some(path) = hooks::hooks_path() else {
+        return;
+    };
+    match hooks::load(&path) {
+        Ok(hooks) => app.hooks = hooks,
+        Err(e) => log::warn!("hooks: {}", e),
+    }
+}
+
+fn load_mpris(app: &mut App) {
+    let Some(path) = mpris::mpris_path() else {
+        return;
+    };
+    match mpris::load(&path) {
+        Ok(mpris) => app.mpris = mpris,
+        Err(e) => log::warn!("mpris: {}", e),
+    }
+}
+
+/// NOTE where to save on exit, None when there is nowhere safe to
+fn load_data(app: &mut App) -> Option<PathBuf> {
+    let path = storage::data_path()?;
+    match storage::load(&path) {
+        Ok(Some(data)) => app.load_saved_data(data),
+        Ok(None) => {}
+        Err(e) => match storage::set_aside(&path) {
+            Ok(aside) => log::error!("{}, moved to {}", e, aside.display()),
+            Err(_) => {
+                log::error!("{}, this session will not be saved", e);
+                return None;
+            }
+        },
+    }
+    Some(path)
+}
+
+/// NOTE a panic on main thread ends the app, terminal is handed back before the panic message
+/// is printed, and a crash report is written next to it. One on a background thread only ends
+/// that thread, the app goes on in the terminal and tells about it in the log
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        log::error!("{}", info);
+        let report = crash::write_report(info);
+        if thread::current().name() != Some("main") {
+            match report {
+                Ok(path) => log::error!("crash report written to {}", path.display()),
+                Err(e) => log::error!("failed to write crash report: {}", e),
+            }
+            return;
+        }
+        let _ = suspend_terminal();
+        default_hook(info);
+        match report {
+            Ok(path) => eprintln!("crash report written to {}", path.display()),
+            Err(e) => eprintln!("failed to write crash report: {}", e),
+        }
+    }));
+}
+
+/// NOTE sleeps until an event comes in or the screen needs drawing again,
+/// draws only after something may have changed
+fn run(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    keymaps: &Keymaps,
+    events: &Events,
+    service: &Service,
+) -> io::Result<()> {
+    terminal.draw(|frame| ui(frame, app, keymaps))?;
     while !app.should_quit {
-        terminal.draw(|frame| ui(frame, &mut app))?;
-        handle_events(
-            &mut app,
-            &TIMER_SETTING_KEYBINDINGS,
-            &TASK_MANAGER_KEYBINDINGS,
-        )?;
-        sleep(Duration::from_millis(interval));
+        let timeout = [
+            app.time_to_next_tick(),
+            sequence_time_left(app),
+            app.time_to_toast_expiry(),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let mut changed = match events.next(timeout) {
+            Some(Event::Input(event)) => handle_event(event, app, keymaps),
+            Some(Event::InputError(e)) => return Err(e),
+            Some(Event::Log(entry)) => {
+                app.log_entry_added(&entry);
+                true
+            }
+            Some(Event::Remote(command)) => {
+                app.run_remote_command(command);
+                true
+            }
+            Some(Event::Signal(SIGTSTP)) => {
+                app.suspend();
+                true
+            }
+            // NOTE the shell may have reset the terminal while stopped
+            Some(Event::Signal(SIGCONT)) => {
+                resume_terminal()?;
+                terminal.clear()?;
+                true
+            }
+            // NOTE SIGINT, SIGTERM and SIGHUP, saved and restored on the way out
+            Some(Event::Signal(signal)) => {
+                log::info!("quitting on signal {}", signal);
+                app.should_quit = true;
+                true
+            }
+            // NOTE a deadline passed: the clock ticked or a key sequence timed out
+            None => true,
+        };
+        handle_sequence_timeout(app, keymaps);
+        if let Some(target) = app.notes_editor_request.take() {
+            if let Some(notes) = app.notes_text(target) {
+                events.pause_input();
+                suspend_terminal()?;
+                let edited = editor::edit_text(&notes).map_err(|e| e.to_string());
+                resume_terminal()?;
+                events.resume_input();
+                // NOTE editor drew over the screen, redraw all of it
+                terminal.clear()?;
+                app.set_notes(target, edited);
+                changed = true;
+            }
+        }
+        if std::mem::take(&mut app.suspend_requested) {
+            events.pause_input();
+            suspend_terminal()?;
+            // NOTE stopped right here until the shell continues us
+            signal_hook::low_level::raise(SIGSTOP)?;
+            resume_terminal()?;
+            events.resume_input();
+            terminal.clear()?;
+            changed = true;
+        }
         app.update();
+        service.update(app.timer_status());
+        if changed && !app.should_quit {
+            terminal.draw(|frame| ui(frame, app, keymaps))?;
+        }
     }
+    Ok(())
+}
 
+/// NOTE hand terminal back to the shell or to a program run from here
+fn suspend_terminal() -> io::Result<()> {
     disable_raw_mode()?;
+    stdout().execute(DisableBracketedPaste)?;
+    stdout().execute(DisableMouseCapture)?;
     stdout().execute(LeaveAlternateScreen)?;
     Ok(())
 }
 
-fn ui(frame: &mut Frame, app: &mut App) {
+fn resume_terminal() -> io::Result<()> {
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    stdout().execute(EnableBracketedPaste)?;
+    stdout().execute(EnableMouseCapture)?;
+    Ok(())
+}
+
+fn ui(frame: &mut Frame, app: &mut App, keymaps: &Keymaps) {
+    frame.render_widget(Block::default().style(app.theme.base_style()), frame.size());
+    let screen = Layout::new(
+        Direction::Vertical,
+        [Constraint::Min(0), Constraint::Length(1)],
+    )
+    .split(frame.size());
     match app.page_selected {
         Page::Normal => {
             let layout = Layout::new(
                 Direction::Horizontal,
                 [Constraint::Ratio(1, 4), Constraint::Ratio(3, 4)],
             )
-            .split(frame.size());
-            render_task_list(frame, layout[0], app);
-            render_right_side(frame, layout[1], app);
+            .split(screen[0]);
+            let left = Layout::new(
+                Direction::Vertical,
+                [Constraint::Ratio(3, 5), Constraint::Ratio(2, 5)],
+            )
+            .split(layout[0]);
+            render_task_list(frame, left[0], app);
+            render_task_details(frame, left[1], app);
+            render_right_side(frame, layout[1], app, keymaps);
         }
         Page::Help => {
-            render_help_screen(frame, frame.size(), app);
+            render_help_screen(frame, screen[0], app, keymaps);
+        }
+        Page::Log => {
+            render_log_page(frame, screen[0], app);
+        }
+        Page::Stats => {
+            render_stats_page(frame, screen[0], app);
+        }
+    }
+    render_status_line(frame, screen[1], app);
+    render_toasts(frame, screen[0], app);
+}
+
+/// NOTE errors stacked in the top right corner, newest at the bottom
+fn render_toasts(frame: &mut Frame, area: Rect, app: &App) {
+    let width = (area.width / 3).max(20).min(area.width);
+    let mut y = area.y;
+    for toast in app.toasts.iter() {
+        let p = Paragraph::new(toast.text.as_str())
+            .block(
+                Block::default()
+                    .title("Error")
+                    .borders(Borders::ALL)
+                    .border_style(Style::new().red()),
+            )
+            .style(app.theme.base_style())
+            .wrap(Wrap { trim: true });
+        // NOTE borders take two lines, text wraps to the width inside them
+        let lines = toast
+            .text
+            .width()
+            .div_ceil(width.saturating_sub(2).max(1) as usize);
+        let height = (lines.max(1) as u16 + 2).min(area.bottom() - y);
+        if height < 3 {
+            break;
+        }
+        let toast_area = Rect::new(area.right() - width, y, width, height);
+        frame.render_widget(Clear, toast_area);
+        frame.render_widget(p, toast_area);
+        y += height;
+    }
+}
+
+fn render_log_page(frame: &mut Frame, area: Rect, app: &mut App) {
+    let entries = logging::recent_entries();
+    let text: Vec<Line> = entries.iter().map(log_entry_line).collect();
+
+    // NOTE borders take two lines
+    app.help_page_size = area.height.saturating_sub(2);
+    let max_scroll = (text.len() as u16).saturating_sub(app.help_page_size);
+    app.help_scroll = app.help_scroll.min(max_scroll);
+
+    let path = logging::log_file_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "not written to a file".to_string());
+    let p = Paragraph::new(text)
+        .block(
+            Block::new()
+                .title("Log")
+                .title(
+                    block::Title::from(path)
+                        .position(block::Position::Bottom)
+                        .alignment(Alignment::Left),
+                )
+                .title(
+                    block::Title::from(format!("{}/{}", app.help_scroll, max_scroll))
+                        .position(block::Position::Bottom)
+                        .alignment(Alignment::Right),
+                )
+                .borders(Borders::ALL),
+        )
+        .style(Style::default())
+        .alignment(Alignment::Left)
+        .scroll((app.help_scroll, 0));
+    frame.render_widget(p, area);
+}
+
+fn render_stats_page(frame: &mut Frame, area: Rect, app: &mut App) {
+    let text: Vec<Line> = app.stats_report().into_iter().map(Line::from).collect();
+
+    // NOTE borders take two lines
+    app.help_page_size = area.height.saturating_sub(2);
+    let max_scroll = (text.len() as u16).saturating_sub(app.help_page_size);
+    app.help_scroll = app.help_scroll.min(max_scroll);
+
+    let p = Paragraph::new(text)
+        .block(
+            Block::new()
+                .title("Stats")
+                .title(
+                    block::Title::from(
+                        "minutes leave pauses out, `:report <file>` writes this page",
+                    )
+                    .position(block::Position::Bottom)
+                    .alignment(Alignment::Left),
+                )
+                .title(
+                    block::Title::from(format!("{}/{}", app.help_scroll, max_scroll))
+                        .position(block::Position::Bottom)
+                        .alignment(Alignment::Right),
+                )
+                .borders(Borders::ALL),
+        )
+        .style(Style::default())
+        .alignment(Alignment::Left)
+        .scroll((app.help_scroll, 0));
+    frame.render_widget(p, area);
+}
+
+fn log_entry_line(entry: &LogEntry) -> Line<'_> {
+    let level_style = match entry.level {
+        log::Level::Error => Style::new().red().bold(),
+        log::Level::Warn => Style::new().yellow(),
+        log::Level::Info => Style::new().green(),
+        log::Level::Debug | log::Level::Trace => Style::new().dark_gray(),
+    };
+    Line::from(vec![
+        Span::styled(
+            entry.time.format("%H:%M:%S ").to_string(),
+            Style::new().blue().italic(),
+        ),
+        Span::styled(format!("{:<6}", entry.level), level_style),
+        Span::styled(format!("{}: ", entry.target), Style::new().dark_gray()),
+        Span::raw(entry.message.as_str()),
+    ])
+}
+
+fn render_status_line(frame: &mut Frame, area: Rect, app: &App) {
+    let line = match (app.mode, &app.message) {
+        (Mode::Command, _) => {
+            let mut spans = vec![Span::raw(":")];
+            spans.extend(app.command_line.input.spans(true));
+            Line::from(spans)
         }
+        (_, Some(message)) => Line::from(message.as_str()),
+        (_, None) => Line::default(),
+    };
+    frame.render_widget(Paragraph::new(line), area);
+
+    if app.mode != Mode::Command || app.command_line.completions.is_empty() {
+        return;
     }
+    // NOTE completions pop up right above command line
+    let completions = &app.command_line.completions;
+    let height = (completions.len().min(8) as u16 + 2).min(area.y);
+    let width = completions.iter().map(|c| c.len()).max().unwrap_or(0) as u16 + 6;
+    let popup = Rect::new(area.x, area.y - height, width.min(area.width), height);
+    let items: Vec<ListItem> = completions
+        .iter()
+        .map(|c| ListItem::new(c.as_str()))
+        .collect();
+    let mut state = ListState::default().with_selected(app.command_line.selected);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .style(app.theme.base_style())
+        .highlight_style(Style::new().reversed())
+        .highlight_symbol("> ");
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut state);
 }
 
 fn render_task_list(frame: &mut Frame, area: Rect, app: &mut App) {
-    let texts: Vec<String> = app
+    // NOTE tasks between Visual mode anchor and selection are highlighted
+    let visual_range = match (app.visual_anchor, app.task_list.state.selected()) {
+        (Some(anchor), Some(idx)) => Some(anchor.min(idx)..=anchor.max(idx)),
+        _ => None,
+    };
+    let items: Vec<ListItem> = app
         .task_list
         .items
         .iter()
-        .map(|t| format!("{} 0/{}", t.title(), t.pomodoros))
+        .enumerate()
+        .map(|(idx, t)| {
+            // NOTE active task is marked and bold, done tasks are crossed out
+            let mark = if app.active_task_id == Some(t.id) {
+                '*'
+            } else {
+                ' '
+            };
+            let mut style = Style::default();
+            if app.active_task_id == Some(t.id) {
+                style = style.bold();
+            }
+            if t.done {
+                style = style.crossed_out().dim();
+            }
+            let item = ListItem::new(format!(
+                "{} {} {}/{}",
+                mark,
+                t.title(),
+                t.completed,
+                t.pomodoros
+            ))
+            .style(style);
+            match visual_range {
+                Some(ref range) if range.contains(&idx) => {
+                    item.style(Style::default().bg(Color::Magenta))
+                }
+                _ => item,
+            }
+        })
         .collect();
-    let list = List::new(texts)
+    let border_style = match app.focus {
+        Focus::TaskList => Style::new().yellow(),
+        Focus::Console => Style::default(),
+    };
+    let list = List::new(items)
         .block(
             Block::default()
                 .title("Task List")
                 .borders(Borders::ALL)
+                .border_style(border_style)
                 .title_position(block::Position::Top)
                 .title_alignment(Alignment::Center)
                 .padding(Padding::vertical(1)),
         )
-        .style(Style::default().fg(Color::White))
+        .style(app.theme.base_style())
         .highlight_style(
             Style::default()
                 .add_modifier(Modifier::ITALIC)
@@ -105,10 +548,63 @@ fn render_task_list(frame: &mut Frame, area: Rect, app: &mut App) {
         .repeat_highlight_symbol(true)
         .direction(ListDirection::TopToBottom);
 
+    // NOTE borders and padding take four lines
+    let page_size = area.height.saturating_sub(4) as usize;
+    app.task_list_page_size = page_size;
+    app.layout.task_list = area.inner(&Margin::new(1, 2));
     frame.render_stateful_widget(list, area, &mut app.task_list.state);
+
+    if app.task_list.items.len() > page_size {
+        let mut scrollbar_state = ScrollbarState::new(app.task_list.items.len())
+            .viewport_content_length(page_size)
+            .position(app.task_list.state.selected().unwrap_or(0));
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            area.inner(&Margin::new(0, 1)),
+            &mut scrollbar_state,
+        );
+    }
+}
+
+fn render_task_details(frame: &mut Frame, area: Rect, app: &App) {
+    let task = app
+        .task_list
+        .state
+        .selected()
+        .and_then(|idx| app.task_list.items.get(idx));
+    let text = match task {
+        Some(task) => {
+            let mut text = vec![
+                Line::from(Span::styled(task.title(), Style::new().bold())),
+                Line::from(vec![
+                    Span::styled("Pomodoros: ", Style::new().green().italic()),
+                    Span::raw(format!("{}/{}", task.completed, task.pomodoros)),
+                    Span::raw(if task.done { "  done" } else { "" }),
+                ]),
+                Line::default(),
+            ];
+            if task.notes.is_empty() {
+                text.push(Line::styled("no notes", Style::new().dim()));
+            } else {
+                text.extend(task.notes.lines().map(Line::from));
+            }
+            text
+        }
+        None => vec![Line::styled("no task selected", Style::new().dim())],
+    };
+    let p = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title("Details")
+                .borders(Borders::ALL)
+                .title_alignment(Alignment::Center),
+        )
+        .style(app.theme.base_style())
+        .wrap(Wrap { trim: false });
+    frame.render_widget(p, area);
 }
 
-fn render_right_side(frame: &mut Frame, area: Rect, app: &App) {
+fn render_right_side(frame: &mut Frame, area: Rect, app: &mut App, keymaps: &Keymaps) {
     let rs = Layout::new(
         Direction::Vertical,
         [Constraint::Ratio(3, 5), Constraint::Ratio(2, 5)],
@@ -116,123 +612,367 @@ fn render_right_side(frame: &mut Frame, area: Rect, app: &App) {
     .split(area);
 
     render_digit_clock(frame, rs[0], app);
+    app.layout.clock = rs[0];
 
-    render_console(frame, rs[1], app);
+    render_console(frame, rs[1], app, keymaps);
 }
 
-fn render_console(frame: &mut Frame, area: Rect, app: &App) {
+fn render_console(frame: &mut Frame, area: Rect, app: &mut App, keymaps: &Keymaps) {
     let layout = Layout::new(
         Direction::Horizontal,
         [Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)],
     )
     .split(area);
 
-    render_state_prompt(frame, layout[0]);
+    render_state_prompt(frame, layout[0], app, keymaps);
     render_user_input_fields(frame, layout[1], app);
 }
 
-#[allow(unused)]
-fn render_help_screen(frame: &mut Frame, area: Rect, app: &App) {
-    let keys_to_help: Vec<String> = TIMER_SETTING_KEYBINDINGS
-        .iter()
-        .filter_map(|(key_event, f)| KEYBINDINGS_HELP_MESSAGE.get(f).map(|help_msg| format!("{:?}: {}", key_event, help_msg)))
-        .collect();
-    let text: Vec<Line<'_>> = keys_to_help
-        .iter()
-        .map(|s| Line::from(vec![Span::raw(s)]))
-        .collect();
+fn render_help_screen(frame: &mut Frame, area: Rect, app: &mut App, keymaps: &Keymaps) {
+    let mut text: Vec<Line<'_>> = Vec::new();
+    if !app.keybinding_problems.is_empty() {
+        text.push(Line::from(Span::styled(
+            "Problems In Keybindings File",
+            Style::new().red().bold(),
+        )));
+        text.extend(
+            app.keybinding_problems
+                .iter()
+                .map(|problem| Line::from(format!("  {}", problem))),
+        );
+        text.push(Line::default());
+    }
+    text.push(Line::from(vec![
+        Span::styled("Leader key: ", Style::new().green().bold()),
+        Span::styled(key_name(&keymaps.leader), Style::new().blue().italic()),
+    ]));
+    text.push(Line::default());
+    for (_, context, keymap) in keymaps.contexts() {
+        text.push(Line::from(Span::styled(
+            context,
+            Style::new().green().bold(),
+        )));
+        for (keys, name, help_msg) in key_hints(keymap) {
+            text.push(Line::from(vec![
+                Span::styled(format!("  {:<24}", keys), Style::new().blue().italic()),
+                Span::raw(format!("{:<44}", help_msg)),
+                Span::styled(name, Style::new().dark_gray()),
+            ]));
+        }
+        text.push(Line::default());
+    }
+    let unbound = unbound_actions(keymaps);
+    if !unbound.is_empty() {
+        text.push(Line::from(Span::styled(
+            "Not Bound To Any Key",
+            Style::new().green().bold(),
+        )));
+        text.extend(
+            unbound
+                .into_iter()
+                .map(|help_msg| Line::from(format!("  {}", help_msg))),
+        );
+    }
+
+    // NOTE borders take two lines
+    app.help_page_size = area.height.saturating_sub(2);
+    let max_scroll = (text.len() as u16).saturating_sub(app.help_page_size);
+    app.help_scroll = app.help_scroll.min(max_scroll);
+
     let p = Paragraph::new(text)
-        .block(Block::new().title("Paragraph").borders(Borders::ALL))
+        .block(
+            Block::new()
+                .title("Help")
+                .title(
+                    block::Title::from(format!("{}/{}", app.help_scroll, max_scroll))
+                        .position(block::Position::Bottom)
+                        .alignment(Alignment::Right),
+                )
+                .borders(Borders::ALL),
+        )
         .style(Style::default())
-        .alignment(Alignment::Center)
-        .wrap(Wrap { trim: true });
+        .alignment(Alignment::Left)
+        .scroll((app.help_scroll, 0));
     frame.render_widget(p, area);
 }
 
-fn render_state_prompt(frame: &mut Frame, area: Rect) {
-    // TODO state prompt with useful information
-    let d1 = Block::default()
-        .title("Usage")
-        .borders(Borders::ALL)
-        .style(Style::default());
-    frame.render_widget(d1, area);
-}
-
-fn render_user_input_fields(frame: &mut Frame, area: Rect, app: &App) {
-    match app.tab_selected {
-        app::Tabs::PomodoroSetting => render_settings(frame, area, app),
-        app::Tabs::TaskManager => render_task_manager(frame, area, app),
+fn render_state_prompt(frame: &mut Frame, area: Rect, app: &App, keymaps: &Keymaps) {
+    let status = if app.is_timer_running() {
+        Span::styled("running", Style::new().green())
+    } else if app.is_timer_paused() {
+        Span::styled("paused", Style::new().yellow())
+    } else {
+        Span::styled("stopped", Style::new().red())
+    };
+    let next_phase = app.next_state();
+    let (pomodoros, tasks_done) = app.statistics();
+    let task = match app.active_task() {
+        Some(task) => {
+            let counts = task.interruption_counts();
+            format!(
+                "{} {}/{}, {}' {}-",
+                task.title(),
+                task.completed,
+                task.pomodoros,
+                counts.internal,
+                counts.external
+            )
+        }
+        None => "no active task".to_string(),
+    };
+    // NOTE `'` internal, `-` external, like the keys logging them
+    let (session, total) = (app.session_interruptions, app.interruption_totals());
+    let interruptions = format!(
+        "{}' {}- this session, {}' {}- on tasks",
+        session.internal, session.external, total.internal, total.external
+    );
+    let finish = match (app.active_task(), app.time_to_finish_task()) {
+        (Some(task), Some(time_left)) => {
+            let time_left = chrono::Duration::from_std(time_left).unwrap_or_default();
+            format!(
+                "~{} ({} left)",
+                (chrono::Local::now() + time_left).format("%H:%M"),
+                task.pomodoros_left()
+            )
+        }
+        (Some(_), None) => "task done".to_string(),
+        (None, _) => "-".to_string(),
     };
-}
 
-fn render_task_manager(frame: &mut Frame, area: Rect, app: &App) {
-    let ((s1, task_name), (s2, pomodoro_per_long_break), (s3, task_notes)) =
-        app.task_manager_input.display();
-    let text = vec![
+    let mut text = vec![
         Line::from(vec![
-            Span::styled(s1, Style::new().green().italic()),
-            Span::from(task_name).style(Style::default()),
+            Span::styled("Phase: ", Style::new().green().italic()),
+            Span::raw(app.state.name()),
+            Span::raw(" "),
+            status,
         ]),
-        Line::default(),
         Line::from(vec![
-            Span::styled(s2, Style::new().green().italic()),
-            Span::from(pomodoro_per_long_break).style(Style::default()),
+            Span::styled("Next: ", Style::new().green().italic()),
+            Span::raw(next_phase.name()),
         ]),
-        Line::default(),
         Line::from(vec![
-            Span::styled(s3, Style::new().green().italic()),
-            Span::from(task_notes).style(Style::default()),
+            Span::styled("Task: ", Style::new().green().italic()),
+            Span::raw(task),
         ]),
-        Line::default(),
-    ];
-    let b = Paragraph::new(text)
-        .block(
-            Block::new()
-                .title("Task Manager --> Pomodoro Settings")
-                .borders(Borders::ALL)
-                .padding(Padding::default()),
-        )
-        .style(Style::default())
-        .alignment(Alignment::Left);
-    frame.render_widget(b, area);
-}
-
-fn render_settings(frame: &mut Frame, area: Rect, app: &App) {
-    let ((s1, timer), (s2, short_break), (s3, long_break), (s4, pomodoro_per_long_break)) =
-        app.timer_setting_input.display();
-    let text = vec![
         Line::from(vec![
-            Span::styled(s1, Style::new().green().italic()),
-            Span::from(timer).style(Style::default()),
-            Span::styled("  min", Style::new().blue().italic()),
+            Span::styled("Finish: ", Style::new().green().italic()),
+            Span::raw(finish),
         ]),
-        Line::default(),
         Line::from(vec![
-            Span::styled(s2, Style::new().green().italic()),
-            Span::from(short_break).style(Style::default()),
-            Span::styled("  min", Style::new().blue().italic()),
+            Span::styled("Done: ", Style::new().green().italic()),
+            Span::raw(format!("{} pomodoros, {} tasks", pomodoros, tasks_done)),
         ]),
-        Line::default(),
         Line::from(vec![
-            Span::styled(s3, Style::new().green().italic()),
-            Span::from(long_break).style(Style::default()),
-            Span::styled("  min", Style::new().blue().italic()),
+            Span::styled("Interruptions: ", Style::new().green().italic()),
+            Span::raw(interruptions),
         ]),
         Line::default(),
-        Line::from(vec![
-            Span::styled(s4, Style::new().green().italic()),
-            Span::from(pomodoro_per_long_break).style(Style::default()),
-        ]),
     ];
-    let b = Paragraph::new(text)
+
+    text.extend(
+        key_hints(keymaps.for_mode(app.mode))
+            .into_iter()
+            .map(|(keys, _, help_msg)| {
+                Line::from(vec![
+                    Span::styled(keys, Style::new().blue().italic()),
+                    Span::raw(" "),
+                    Span::raw(help_msg),
+                ])
+            }),
+    );
+
+    // NOTE mode indicator, followed by count and key sequence typed so far
+    let mut pending = String::new();
+    if let Some(count) = app.count {
+        pending.push_str(&count.to_string());
+        pending.push(' ');
+    }
+    pending.push_str(&sequence_name(&app.pending_keys));
+    let mode_style = match app.mode {
+        Mode::Normal => Style::new().black().on_blue(),
+        Mode::Insert => Style::new().black().on_green(),
+        Mode::Visual => Style::new().black().on_magenta(),
+        Mode::Command => Style::new().black().on_yellow(),
+    };
+    let p = Paragraph::new(text)
         .block(
-            Block::new()
-                .title("Pomodoro Settings --> Task Manager")
+            Block::default()
+                .title("Usage")
+                .title(
+                    block::Title::from(Line::from(vec![
+                        Span::styled(format!(" {} ", app.mode.name()), mode_style),
+                        Span::raw(format!(" {}", pending)),
+                    ]))
+                    .position(block::Position::Bottom),
+                )
                 .borders(Borders::ALL)
-                .padding(Padding::default()),
+                .style(Style::default()),
         )
-        .style(Style::default())
-        .alignment(Alignment::Left);
-    // .wrap(Wrap { trim: true });
-    frame.render_widget(b, area);
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(p, area);
+}
+
+fn render_user_input_fields(frame: &mut Frame, area: Rect, app: &mut App) {
+    let (current, other) = match app.tab_selected {
+        app::Tabs::PomodoroSetting => {
+            if app.timer_setting_input.is_modified() {
+                ("Pomodoro Settings (unsaved)".to_string(), "Task Manager")
+            } else {
+                ("Pomodoro Settings".to_string(), "Task Manager")
+            }
+        }
+        app::Tabs::TaskManager => match app
+            .editing_task_id
+            .and_then(|id| app.task_list.items.iter().find(|task| task.id == id))
+        {
+            Some(task) => (format!("Editing {}", task.title()), "Pomodoro Settings"),
+            None => ("Task Manager".to_string(), "Pomodoro Settings"),
+        },
+    };
+    let title = format!("{} --> {}", current, other);
+    // NOTE title starts after the top left corner, clicking the other tab's name switches to it
+    let other_x = area.x + 1 + (title.width() - other.width()) as u16;
+    let other_width = (other.width() as u16).min(area.right().saturating_sub(other_x + 1));
+    app.layout.other_tab = Rect::new(other_x, area.y, other_width, 1);
+
+    let block = Block::new()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(console_border_style(app))
+        .padding(Padding::default());
+    let mut inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let insert = app.mode == Mode::Insert;
+    let form = match app.tab_selected {
+        app::Tabs::PomodoroSetting => {
+            let layout = Layout::new(
+                Direction::Vertical,
+                [Constraint::Length(2), Constraint::Min(0)],
+            )
+            .split(inner);
+            render_preset_picker(frame, layout[0], app);
+            inner = layout[1];
+            &mut app.timer_setting_input
+        }
+        app::Tabs::TaskManager => {
+            app.layout.presets.clear();
+            &mut app.task_manager_input
+        }
+    };
+    app.layout.fields = render_form(frame, inner, form, insert);
+}
+
+/// NOTE preset names in a row, the one in use highlighted, `[`/`]` or a click switches.
+/// Below them, the active task's own timer settings when it has any
+fn render_preset_picker(frame: &mut Frame, area: Rect, app: &mut App) {
+    let current = app.current_preset();
+    let mut spans = vec![Span::styled("Presets: ", Style::new().green().italic())];
+    let mut x = area.x + spans[0].width() as u16;
+    app.layout.presets.clear();
+    for (idx, preset) in app.presets.iter().enumerate() {
+        let name = format!(" {} ", preset.name);
+        let width = (name.width() as u16).min(area.right().saturating_sub(x));
+        app.layout.presets.push(Rect::new(x, area.y, width, 1));
+        x += width;
+        let style = if current == Some(idx) {
+            Style::new().black().on_green()
+        } else {
+            Style::new().blue()
+        };
+        spans.push(Span::styled(name, style));
+    }
+    if current.is_none() {
+        spans.push(Span::styled(" custom", Style::new().dim()));
+    }
+    let mut lines = vec![Line::from(spans)];
+    if let Some(task) = app.active_task().filter(|task| !task.timer.is_empty()) {
+        lines.push(Line::styled(
+            format!("Overridden by {}: {}", task.title(), task.timer),
+            Style::new().yellow(),
+        ));
+    }
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+fn console_border_style(app: &App) -> Style {
+    match app.focus {
+        Focus::Console => Style::new().yellow(),
+        Focus::TaskList => Style::default(),
+    }
+}
+
+/// NOTE the selected field is marked
+fn field_label_spans(field: &Field, selected: bool) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    if selected {
+        spans.push(Span::styled(">> ", Style::new().green().italic()));
+    }
+    spans.push(Span::styled(
+        field.spec.label,
+        Style::new().green().italic(),
+    ));
+    spans
+}
+
+/// NOTE unsaved edits get a `*` and rejected values their error
+fn field_status_spans(field: &Field) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    if field.is_modified() {
+        spans.push(Span::styled(" *", Style::new().yellow().bold()));
+    }
+    if let Some(error) = &field.error {
+        spans.push(Span::styled(format!("  {}", error), Style::new().red()));
+    }
+    spans
+}
+
+/// NOTE single line fields take two lines each, notes share what is left,
+/// returns where each field was drawn
+fn render_form(frame: &mut Frame, area: Rect, form: &mut Form, insert: bool) -> Vec<Rect> {
+    let constraints: Vec<Constraint> = form
+        .fields()
+        .iter()
+        .map(|field| match field.editor {
+            Editor::Line(_) => Constraint::Length(2),
+            Editor::Area(_) => Constraint::Min(2),
+        })
+        .collect();
+    let layout = Layout::new(Direction::Vertical, constraints).split(area);
+    let selected: Vec<bool> = (0..form.fields().len())
+        .map(|idx| form.is_selected(idx))
+        .collect();
+    for (idx, field) in form.fields_mut().iter_mut().enumerate() {
+        let rect = layout[idx];
+        let show_cursor = selected[idx] && insert;
+        let mut spans = field_label_spans(field, selected[idx]);
+        let status = field_status_spans(field);
+        match &mut field.editor {
+            Editor::Line(line) => {
+                spans.extend(line.spans(show_cursor));
+                if let Some(unit) = field.spec.unit() {
+                    spans.push(Span::styled(
+                        format!("  {}", unit),
+                        Style::new().blue().italic(),
+                    ));
+                }
+                spans.extend(status);
+                frame.render_widget(Paragraph::new(Line::from(spans)), rect);
+            }
+            Editor::Area(notes) => {
+                let layout = Layout::new(
+                    Direction::Vertical,
+                    [Constraint::Length(1), Constraint::Min(0)],
+                )
+                .split(rect);
+                let notes_area = layout[1].inner(&Margin::new(1, 0));
+                let lines = notes.render_lines(notes_area.width, notes_area.height, show_cursor);
+                spans.extend(status);
+                frame.render_widget(Paragraph::new(Line::from(spans)), layout[0]);
+                frame.render_widget(Paragraph::new(lines), notes_area);
+            }
+        }
+    }
+    layout.to_vec()
 }
@@ -0,0 +1,513 @@
+use crate::config;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use zbus::blocking::{Connection, ConnectionBuilder};
+use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+
+const PLAYER_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const PLAYER_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const PLAYLISTS_INTERFACE: &str = "org.mpris.MediaPlayer2.Playlists";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum PlayerAction {
+    Play,
+    Pause,
+    Stop,
+}
+
+impl PlayerAction {
+    fn method(&self) -> &'static str {
+        match self {
+            PlayerAction::Play => "Play",
+            PlayerAction::Pause => "Pause",
+            PlayerAction::Stop => "Stop",
+        }
+    }
+}
+
+/// NOTE what players do when a phase starts, a playlist is switched to before the action
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PhaseRule {
+    action: Option<PlayerAction>,
+    // NOTE object path of the playlist, as the player lists it
+    playlist: Option<String>,
+}
+
+/// NOTE `$XDG_CONFIG_HOME/pomodoro/mpris.toml`, players are left alone without it:
+///
+/// ```toml
+/// # name after `org.mpris.MediaPlayer2.`, every player when not given
+/// player = "spotify"
+/// # D-Bus address to use instead of the session bus
+/// bus = "unix:path=/tmp/test-bus"
+///
+/// [pomodoro]
+/// action = "play"
+///
+/// [short_break]
+/// action = "pause"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MprisFile {
+    player: Option<String>,
+    bus: Option<String>,
+    pomodoro: Option<PhaseRule>,
+    short_break: Option<PhaseRule>,
+    long_break: Option<PhaseRule>,
+}
+
+impl MprisFile {
+    fn rule(&self, phase: &str) -> Option<&PhaseRule> {
+        match phase {
+            "pomodoro" => self.pomodoro.as_ref(),
+            "short_break" => self.short_break.as_ref(),
+            "long_break" => self.long_break.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        for (phase, rule) in [
+            ("pomodoro", &self.pomodoro),
+            ("short_break", &self.short_break),
+            ("long_break", &self.long_break),
+        ] {
+            if let Some(playlist) = rule.as_ref().and_then(|rule| rule.playlist.as_deref()) {
+                ObjectPath::try_from(playlist)
+                    .map_err(|e| format!("{} playlist `{}`: {}", phase, playlist, e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// NOTE hands phases to the mpris thread, which owns the bus connection and remembers
+/// which players it paused
+#[derive(Debug)]
+pub struct Mpris {
+    sender: Sender<&'static str>,
+}
+
+impl Mpris {
+    fn start(file: MprisFile) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("mpris".to_string())
+            .spawn(move || run(file, receiver))?;
+        Ok(Self { sender })
+    }
+
+    /// NOTE `phase` is the id of a state, e.g. `short_break`
+    pub fn phase_started(&self, phase: &'static str) {
+        if self.sender.send(phase).is_err() {
+            log::error!("mpris thread is gone, players are left alone");
+        }
+    }
+}
+
+/// NOTE connects on first use, and again after a failed call, players come and go.
+/// A phase told twice in a row is acted on once
+fn run(file: MprisFile, phases: Receiver<&'static str>) {
+    let mut connection: Option<Connection> = None;
+    let mut last_phase = None;
+    // NOTE players this app paused or stopped, the only ones `play` resumes
+    let mut paused: Vec<String> = Vec::new();
+    for phase in phases {
+        if last_phase.replace(phase) == Some(phase) {
+            continue;
+        }
+        let Some(rule) = file.rule(phase) else {
+            continue;
+        };
+        if connection.is_none() {
+            match connect(file.bus.as_deref()) {
+                Ok(new) => connection = Some(new),
+                Err(e) => {
+                    log::warn!("mpris: failed to connect to D-Bus: {}", e);
+                    continue;
+                }
+            }
+        }
+        let Some(ref conn) = connection else {
+            continue;
+        };
+        if let Err(e) = apply_rule(conn, file.player.as_deref(), phase, rule, &mut paused) {
+            log::warn!("mpris: {}", e);
+            if matches!(e, RuleError::Bus(_)) {
+                connection = None;
+            }
+        }
+    }
+}
+
+fn connect(bus: Option<&str>) -> zbus::Result<Connection> {
+    match bus {
+        Some(address) => ConnectionBuilder::address(address)?.build(),
+        None => Connection::session(),
+    }
+}
+
+enum RuleError {
+    // NOTE the bus itself failed, connection is made again next time
+    Bus(zbus::Error),
+    // NOTE one player failed, others were still told
+    Players(Vec<String>),
+}
+
+impl std::fmt::Display for RuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RuleError::Bus(e) => write!(f, "failed to list players: {}", e),
+            RuleError::Players(errors) => write!(f, "{}", errors.join(", ")),
+        }
+    }
+}
+
+/// NOTE player names on the bus, `org.mpris.MediaPlayer2.vlc.instance42` matches `vlc`
+fn players(conn: &Connection, player: Option<&str>) -> zbus::Result<Vec<String>> {
+    let names: Vec<String> = conn
+        .call_method(
+            Some("org.freedesktop.DBus"),
+            "/org/freedesktop/DBus",
+            Some("org.freedesktop.DBus"),
+            "ListNames",
+            &(),
+        )?
+        .body()?;
+    Ok(names
+        .into_iter()
+        .filter(|name| match (name.strip_prefix(PLAYER_PREFIX), player) {
+            (Some(_), None) => true,
+            (Some(rest), Some(player)) => {
+                rest == player || rest.starts_with(&format!("{}.", player))
+            }
+            (None, _) => false,
+        })
+        .collect())
+}
+
+fn is_playing(conn: &Connection, name: &str) -> zbus::Result<bool> {
+    let status: OwnedValue = conn
+        .call_method(
+            Some(name),
+            PLAYER_PATH,
+            Some("org.freedesktop.DBus.Properties"),
+            "Get",
+            &(PLAYER_INTERFACE, "PlaybackStatus"),
+        )?
+        .body()?;
+    Ok(matches!(&*status, Value::Str(status) if status.as_str() == "Playing"))
+}
+
+/// NOTE `pause` and `stop` only go to players that are playing, `play` only to the ones
+/// paused that way, so music the user paused stays paused
+fn apply_rule(
+    conn: &Connection,
+    player: Option<&str>,
+    phase: &str,
+    rule: &PhaseRule,
+    paused: &mut Vec<String>,
+) -> Result<(), RuleError> {
+    let names = players(conn, player).map_err(RuleError::Bus)?;
+    // NOTE a player that quit is a new one when it comes back
+    paused.retain(|name| names.contains(name));
+    if names.is_empty() {
+        log::debug!("mpris: no player for {} to act on", phase);
+        return Ok(());
+    }
+    let mut errors = Vec::new();
+    for name in names.iter() {
+        if let Some(ref playlist) = rule.playlist {
+            // NOTE checked when mpris file was loaded
+            let Ok(path) = ObjectPath::try_from(playlist.as_str()) else {
+                continue;
+            };
+            if let Err(e) = conn.call_method(
+                Some(name.as_str()),
+                PLAYER_PATH,
+                Some(PLAYLISTS_INTERFACE),
+                "ActivatePlaylist",
+                &(path,),
+            ) {
+                errors.push(format!("{} failed to switch playlist: {}", name, e));
+            }
+        }
+        let Some(action) = rule.action else {
+            continue;
+        };
+        let wanted = match action {
+            PlayerAction::Play => paused.contains(name),
+            PlayerAction::Pause | PlayerAction::Stop => match is_playing(conn, name) {
+                Ok(playing) => playing,
+                Err(e) => {
+                    errors.push(format!("{} failed to tell its status: {}", name, e));
+                    continue;
+                }
+            },
+        };
+        if !wanted {
+            log::debug!("mpris: {} left alone for {}", name, phase);
+            continue;
+        }
+        match conn.call_method(
+            Some(name.as_str()),
+            PLAYER_PATH,
+            Some(PLAYER_INTERFACE),
+            action.method(),
+            &(),
+        ) {
+            Ok(_) => {
+                log::debug!("mpris: {} {} for {}", name, action.method(), phase);
+                match action {
+                    PlayerAction::Play => paused.retain(|paused| paused != name),
+                    PlayerAction::Pause | PlayerAction::Stop if !paused.contains(name) => {
+                        paused.push(name.clone())
+                    }
+                    PlayerAction::Pause | PlayerAction::Stop => {}
+                }
+            }
+            Err(e) => errors.push(format!("{} failed to {}: {}", name, action.method(), e)),
+        }
+    }
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(RuleError::Players(errors)),
+    }
+}
+
+pub fn mpris_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("mpris.toml"))
+}
+
+/// NOTE None when there is no mpris file, players are left alone then
+pub fn load(path: &Path) -> Result<Option<Mpris>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+    };
+    let file: MprisFile = toml::from_str(&text)
+        .map_err(|e| format!("invalid mpris file {}: {}", path.display(), e))?;
+    file.check()
+        .map_err(|e| format!("invalid mpris file {}: {}", path.display(), e))?;
+    Mpris::start(file)
+        .map(Some)
+        .map_err(|e| format!("failed to start mpris thread: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use zbus::dbus_interface;
+
+    type Calls = Arc<Mutex<Vec<String>>>;
+
+    /// NOTE private bus, killed when the test is done
+    struct Bus {
+        daemon: Child,
+        address: String,
+        _dir: TempDir,
+    }
+
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    /// NOTE None when there is no `dbus-daemon` to run
+    fn private_bus() -> Option<Bus> {
+        let dir = std::env::temp_dir().join(format!("pomodoro-mpris-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dir = TempDir(dir);
+        let spawned = Command::new("dbus-daemon")
+            .arg("--session")
+            .arg(format!(
+                "--address=unix:path={}",
+                dir.0.join("bus").display()
+            ))
+            .arg("--nofork")
+            .arg("--print-address=1")
+            .stdout(Stdio::piped())
+            .spawn();
+        let mut daemon = match spawned {
+            Ok(daemon) => daemon,
+            Err(e) => {
+                eprintln!("skipped, no dbus-daemon: {}", e);
+                return None;
+            }
+        };
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take().unwrap())
+            .read_line(&mut address)
+            .unwrap();
+        Some(Bus {
+            daemon,
+            address: address.trim().to_string(),
+            _dir: dir,
+        })
+    }
+
+    struct MockPlayer {
+        name: &'static str,
+        playing: Arc<Mutex<bool>>,
+        calls: Calls,
+    }
+
+    impl MockPlayer {
+        fn record(&self, call: String) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", self.name, call));
+        }
+    }
+
+    #[dbus_interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl MockPlayer {
+        fn play(&self) {
+            *self.playing.lock().unwrap() = true;
+            self.record("Play".to_string());
+        }
+
+        fn pause(&self) {
+            *self.playing.lock().unwrap() = false;
+            self.record("Pause".to_string());
+        }
+
+        fn stop(&self) {
+            *self.playing.lock().unwrap() = false;
+            self.record("Stop".to_string());
+        }
+
+        #[dbus_interface(property)]
+        fn playback_status(&self) -> String {
+            match *self.playing.lock().unwrap() {
+                true => "Playing".to_string(),
+                false => "Paused".to_string(),
+            }
+        }
+    }
+
+    struct MockPlaylists {
+        name: &'static str,
+        calls: Calls,
+    }
+
+    #[dbus_interface(name = "org.mpris.MediaPlayer2.Playlists")]
+    impl MockPlaylists {
+        fn activate_playlist(&self, playlist: ObjectPath<'_>) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{} ActivatePlaylist {}", self.name, playlist));
+        }
+    }
+
+    fn serve_player(address: &str, name: &'static str, playing: bool, calls: &Calls) -> Connection {
+        let player = MockPlayer {
+            name,
+            playing: Arc::new(Mutex::new(playing)),
+            calls: calls.clone(),
+        };
+        let playlists = MockPlaylists {
+            name,
+            calls: calls.clone(),
+        };
+        ConnectionBuilder::address(address)
+            .unwrap()
+            .name(format!("{}{}", PLAYER_PREFIX, name))
+            .unwrap()
+            .serve_at(PLAYER_PATH, player)
+            .unwrap()
+            .serve_at(PLAYER_PATH, playlists)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    /// NOTE calls that arrive for `phase`, waiting until `count` of them did
+    fn calls_for(mpris: &Mpris, calls: &Calls, phase: &'static str, count: usize) -> Vec<String> {
+        mpris.phase_started(phase);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while calls.lock().unwrap().len() < count && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        // NOTE time for calls that should not come
+        thread::sleep(Duration::from_millis(200));
+        let mut arrived: Vec<String> = calls.lock().unwrap().drain(..).collect();
+        arrived.sort();
+        arrived
+    }
+
+    #[test]
+    fn phases_drive_players() {
+        let Some(bus) = private_bus() else {
+            return;
+        };
+        let calls = Calls::default();
+        // NOTE `music` plays, `podcast` was paused by the user
+        let _music = serve_player(&bus.address, "music", true, &calls);
+        let _podcast = serve_player(&bus.address, "podcast", false, &calls);
+        let file: MprisFile = toml::from_str(&format!(
+            r#"
+            bus = "{}"
+
+            [pomodoro]
+            action = "play"
+            playlist = "/focus"
+
+            [short_break]
+            action = "pause"
+
+            [long_break]
+            action = "stop"
+            "#,
+            bus.address
+        ))
+        .unwrap();
+        let mpris = Mpris::start(file).unwrap();
+
+        // NOTE nothing was paused by pomodoro yet, so nothing to resume
+        assert_eq!(
+            calls_for(&mpris, &calls, "pomodoro", 2),
+            [
+                "music ActivatePlaylist /focus",
+                "podcast ActivatePlaylist /focus"
+            ]
+        );
+        assert_eq!(calls_for(&mpris, &calls, "short_break", 1), ["music Pause"]);
+        // NOTE told again as its timer is launched
+        assert!(calls_for(&mpris, &calls, "short_break", 0).is_empty());
+        assert_eq!(
+            calls_for(&mpris, &calls, "pomodoro", 3),
+            [
+                "music ActivatePlaylist /focus",
+                "music Play",
+                "podcast ActivatePlaylist /focus"
+            ]
+        );
+        assert_eq!(calls_for(&mpris, &calls, "long_break", 1), ["music Stop"]);
+    }
+}
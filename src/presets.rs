@@ -0,0 +1,371 @@
+use crate::config;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+// NOTE same range as the settings fields
+const SETTING_RANGE: RangeInclusive<u64> = 1..=99;
+
+/// NOTE lengths in minutes, like in settings fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PresetLengths {
+    pub pomodoro: u64,
+    pub short_break: u64,
+    pub long_break: u64,
+    pub pomodoros_per_long_break: u64,
+}
+
+impl PresetLengths {
+    /// NOTE as (settings field name, value), the way `:set` takes them
+    pub fn fields(&self) -> [(&'static str, u64); 4] {
+        [
+            ("pomodoro", self.pomodoro),
+            ("short_break", self.short_break),
+            ("long_break", self.long_break),
+            ("pomodoros_per_long_break", self.pomodoros_per_long_break),
+        ]
+    }
+
+    /// NOTE the first setting out of range, settings are multiplied into durations unchecked
+    fn check(&self) -> Result<(), String> {
+        self.fields()
+            .into_iter()
+            .try_for_each(|(name, value)| check_setting(name, value))
+    }
+}
+
+/// NOTE `name` is a settings field name, `value` in its units
+pub fn check_setting(name: &str, value: u64) -> Result<(), String> {
+    match SETTING_RANGE.contains(&value) {
+        true => Ok(()),
+        false => Err(out_of_range(name)),
+    }
+}
+
+fn out_of_range(name: &str) -> String {
+    format!(
+        "{} must be between {} and {}",
+        name,
+        SETTING_RANGE.start(),
+        SETTING_RANGE.end()
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct Preset {
+    pub name: String,
+    // NOTE built-in presets can not be overwritten, and are not written to presets file
+    pub builtin: bool,
+    pub lengths: PresetLengths,
+}
+
+const BUILTIN_PRESETS: [(&str, PresetLengths); 3] = [
+    (
+        "classic",
+        PresetLengths {
+            pomodoro: 25,
+            short_break: 5,
+            long_break: 15,
+            pomodoros_per_long_break: 4,
+        },
+    ),
+    (
+        "deep-work",
+        PresetLengths {
+            pomodoro: 50,
+            short_break: 10,
+            long_break: 30,
+            pomodoros_per_long_break: 3,
+        },
+    ),
+    (
+        "sprint",
+        PresetLengths {
+            pomodoro: 15,
+            short_break: 3,
+            long_break: 10,
+            pomodoros_per_long_break: 4,
+        },
+    ),
+];
+
+/// NOTE `$XDG_CONFIG_HOME/pomodoro/presets.toml`, one table per preset:
+///
+/// ```toml
+/// [reading]
+/// pomodoro = 40
+/// short_break = 5
+/// long_break = 20
+/// pomodoros_per_long_break = 3
+/// ```
+pub fn presets_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("presets.toml"))
+}
+
+/// NOTE built-ins first, then the ones in presets file, along with every problem found in it.
+/// A preset named like a built-in one is skipped
+pub fn load(path: Option<&Path>) -> (Vec<Preset>, Vec<String>) {
+    let mut presets: Vec<Preset> = BUILTIN_PRESETS
+        .iter()
+        .map(|(name, lengths)| Preset {
+            name: name.to_string(),
+            builtin: true,
+            lengths: *lengths,
+        })
+        .collect();
+    let Some(path) = path else {
+        return (presets, Vec::new());
+    };
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return (presets, Vec::new()),
+        Err(e) => {
+            return (
+                presets,
+                vec![format!("failed to read {}: {}", path.display(), e)],
+            )
+        }
+    };
+    let file: BTreeMap<String, PresetLengths> = match toml::from_str(&text) {
+        Ok(file) => file,
+        Err(e) => return (presets, vec![format!("invalid presets file: {}", e)]),
+    };
+    let mut problems = Vec::new();
+    for (name, lengths) in file {
+        if presets.iter().any(|preset| preset.name == name) {
+            problems.push(format!("preset `{}` is built in", name));
+            continue;
+        }
+        if let Err(e) = lengths.check() {
+            problems.push(format!("preset `{}`: {}", name, e));
+            continue;
+        }
+        presets.push(Preset {
+            name,
+            builtin: false,
+            lengths,
+        });
+    }
+    (presets, problems)
+}
+
+/// NOTE rewrites presets file with the presets that are not built in
+pub fn save(path: &Path, presets: &[Preset]) -> io::Result<()> {
+    let file: BTreeMap<&str, PresetLengths> = presets
+        .iter()
+        .filter(|preset| !preset.builtin)
+        .map(|preset| (preset.name.as_str(), preset.lengths))
+        .collect();
+    let text = toml::to_string(&file).map_err(io::Error::other)?;
+    storage::write_file(path, &text)
+}
+
+/// NOTE a task's own timer settings, on top of the settings in use, or of a preset when it names one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimerOverride {
+    pub preset: Option<String>,
+    pub pomodoro: Option<u64>,
+    pub short_break: Option<u64>,
+    pub long_break: Option<u64>,
+    pub pomodoros_per_long_break: Option<u64>,
+}
+
+impl TimerOverride {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn fields(&self) -> [(&'static str, Option<u64>); 4] {
+        [
+            ("pomodoro", self.pomodoro),
+            ("short_break", self.short_break),
+            ("long_break", self.long_break),
+            ("pomodoros_per_long_break", self.pomodoros_per_long_break),
+        ]
+    }
+
+    /// NOTE a preset name and `<setting>=<value>` pairs, e.g. `deep-work pomodoro=45`,
+    /// settings are the ones `:set` takes and values are in its range
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        for word in text.split_whitespace() {
+            let Some((name, value)) = word.split_once('=') else {
+                if parsed.preset.is_some() {
+                    return Err("only one preset".to_string());
+                }
+                parsed.preset = Some(word.to_string());
+                continue;
+            };
+            let value = match value.parse::<u64>() {
+                Ok(value) if SETTING_RANGE.contains(&value) => value,
+                _ => return Err(out_of_range(name)),
+            };
+            let setting = match name {
+                "pomodoro" => &mut parsed.pomodoro,
+                "short_break" => &mut parsed.short_break,
+                "long_break" => &mut parsed.long_break,
+                "pomodoros_per_long_break" => &mut parsed.pomodoros_per_long_break,
+                _ => return Err(format!("unknown setting `{}`", name)),
+            };
+            *setting = Some(value);
+        }
+        Ok(parsed)
+    }
+}
+
+/// NOTE in the form `parse` takes
+impl fmt::Display for TimerOverride {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut words: Vec<String> = self.preset.iter().cloned().collect();
+        for (name, value) in self.fields() {
+            if let Some(value) = value {
+                words.push(format!("{}={}", name, value));
+            }
+        }
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LENGTHS: PresetLengths = PresetLengths {
+        pomodoro: 40,
+        short_break: 5,
+        long_break: 20,
+        pomodoros_per_long_break: 3,
+    };
+
+    /// NOTE presets file of its own for each test, removed when done
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, text: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "pomodoro-presets-{}-{}.toml",
+                std::process::id(),
+                name
+            ));
+            fs::write(&path, text).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn check_names_the_first_setting_out_of_range() {
+        assert_eq!(LENGTHS.check(), Ok(()));
+        let lengths = PresetLengths {
+            short_break: 0,
+            long_break: 100,
+            ..LENGTHS
+        };
+        assert_eq!(
+            lengths.check(),
+            Err("short_break must be between 1 and 99".to_string())
+        );
+        assert_eq!(check_setting("pomodoro", 99), Ok(()));
+    }
+
+    #[test]
+    fn load_without_a_file_gives_builtins() {
+        let (presets, problems) = load(None);
+        assert!(problems.is_empty());
+        let names: Vec<&str> = presets.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["classic", "deep-work", "sprint"]);
+        assert!(presets.iter().all(|preset| preset.builtin));
+
+        let missing = std::env::temp_dir().join("pomodoro-presets-missing.toml");
+        assert!(load(Some(&missing)).1.is_empty());
+    }
+
+    #[test]
+    fn load_skips_presets_with_problems() {
+        let file = TempFile::new(
+            "problems",
+            "[classic]\npomodoro = 1\nshort_break = 1\nlong_break = 1\npomodoros_per_long_break = 1\n\n\
+             [long]\npomodoro = 120\nshort_break = 5\nlong_break = 20\npomodoros_per_long_break = 3\n\n\
+             [reading]\npomodoro = 40\nshort_break = 5\nlong_break = 20\npomodoros_per_long_break = 3\n",
+        );
+        let (presets, problems) = load(Some(&file.0));
+        assert_eq!(
+            problems,
+            [
+                "preset `classic` is built in",
+                "preset `long`: pomodoro must be between 1 and 99"
+            ]
+        );
+        let last = presets.last().unwrap();
+        assert_eq!((last.name.as_str(), last.builtin), ("reading", false));
+        assert_eq!(last.lengths, LENGTHS);
+    }
+
+    #[test]
+    fn load_rejects_unknown_keys() {
+        let file = TempFile::new("unknown", "[reading]\npomodoro = 40\nlunch = 60\n");
+        let (presets, problems) = load(Some(&file.0));
+        assert_eq!(presets.len(), BUILTIN_PRESETS.len());
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("invalid presets file"));
+    }
+
+    #[test]
+    fn save_writes_only_user_presets() {
+        let file = TempFile::new("save", "");
+        let (mut presets, _) = load(None);
+        presets.push(Preset {
+            name: "reading".to_string(),
+            builtin: false,
+            lengths: LENGTHS,
+        });
+        save(&file.0, &presets).unwrap();
+        let text = fs::read_to_string(&file.0).unwrap();
+        assert!(!text.contains("classic"));
+        let (loaded, problems) = load(Some(&file.0));
+        assert!(problems.is_empty());
+        assert_eq!(loaded.len(), presets.len());
+    }
+
+    #[test]
+    fn timer_override_reads_a_preset_and_settings() {
+        let parsed = TimerOverride::parse("  deep-work pomodoro=45  long_break=20").unwrap();
+        assert_eq!(parsed.preset.as_deref(), Some("deep-work"));
+        assert_eq!((parsed.pomodoro, parsed.long_break), (Some(45), Some(20)));
+        assert_eq!(parsed.short_break, None);
+        assert_eq!(parsed.to_string(), "deep-work pomodoro=45 long_break=20");
+        assert!(TimerOverride::parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn timer_override_rejects_bad_words() {
+        assert_eq!(
+            TimerOverride::parse("classic sprint"),
+            Err("only one preset".to_string())
+        );
+        assert_eq!(
+            TimerOverride::parse("pomodoro=0"),
+            Err("pomodoro must be between 1 and 99".to_string())
+        );
+        assert_eq!(
+            TimerOverride::parse("short_break=five"),
+            Err("short_break must be between 1 and 99".to_string())
+        );
+        assert_eq!(
+            TimerOverride::parse("lunch=30"),
+            Err("unknown setting `lunch`".to_string())
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use crate::app::TASK_ESTIMATE_MAX;
+use crate::event::Event;
+use crate::sync::lock;
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use zbus::blocking::{Connection, ConnectionBuilder};
+use zbus::dbus_interface;
+use zbus::fdo;
+use zbus::zvariant::Value;
+
+/// NOTE well-known name on the session bus, also the interface name
+pub const BUS_NAME: &str = "org.pomodoro.Timer";
+const OBJECT_PATH: &str = "/org/pomodoro/Timer";
+
+/// NOTE asked for over D-Bus, carried out by main loop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteCommand {
+    // NOTE launches the timer, or resumes it when paused
+    Start,
+    Pause,
+    Toggle,
+    Skip,
+    AddTask { title: String, pomodoros: u64 },
+}
+
+/// NOTE what the service shows as properties, taken from the app after every update
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimerStatus {
+    // NOTE `pomodoro`, `short_break` or `long_break`
+    pub phase: &'static str,
+    pub running: bool,
+    pub paused: bool,
+    // NOTE seconds
+    pub remaining: u64,
+    // NOTE empty when no task is active
+    pub active_task: String,
+}
+
+impl TimerStatus {
+    /// NOTE properties that differ from `old`, by their D-Bus names
+    fn changes(&self, old: &TimerStatus) -> HashMap<&'static str, Value<'static>> {
+        let mut changed = HashMap::new();
+        if self.phase != old.phase {
+            changed.insert("Phase", Value::from(self.phase.to_string()));
+        }
+        if self.running != old.running {
+            changed.insert("Running", Value::from(self.running));
+        }
+        if self.paused != old.paused {
+            changed.insert("Paused", Value::from(self.paused));
+        }
+        if self.remaining != old.remaining {
+            changed.insert("Remaining", Value::from(self.remaining));
+        }
+        if self.active_task != old.active_task {
+            changed.insert("ActiveTask", Value::from(self.active_task.clone()));
+        }
+        changed
+    }
+}
+
+// NOTE calls are answered on zbus threads, status is shared with main thread
+struct TimerInterface {
+    status: Arc<Mutex<TimerStatus>>,
+    sender: Sender<Event>,
+}
+
+impl TimerInterface {
+    fn send(&self, command: RemoteCommand) -> fdo::Result<()> {
+        self.sender
+            .send(Event::Remote(command))
+            .map_err(|_| fdo::Error::Failed("pomodoro is quitting".to_string()))
+    }
+}
+
+#[dbus_interface(name = "org.pomodoro.Timer")]
+impl TimerInterface {
+    fn start(&self) -> fdo::Result<()> {
+        self.send(RemoteCommand::Start)
+    }
+
+    fn pause(&self) -> fdo::Result<()> {
+        self.send(RemoteCommand::Pause)
+    }
+
+    fn toggle(&self) -> fdo::Result<()> {
+        self.send(RemoteCommand::Toggle)
+    }
+
+    fn skip(&self) -> fdo::Result<()> {
+        self.send(RemoteCommand::Skip)
+    }
+
+    /// NOTE `pomodoros` of 0 means the default estimate
+    fn add_task(&self, title: String, pomodoros: u64) -> fdo::Result<()> {
+        let title = title.trim().to_string();
+        if title.is_empty() {
+            return Err(fdo::Error::InvalidArgs("title is required".to_string()));
+        }
+        if pomodoros > TASK_ESTIMATE_MAX {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "pomodoros must be at most {}",
+                TASK_ESTIMATE_MAX
+            )));
+        }
+        self.send(RemoteCommand::AddTask { title, pomodoros })
+    }
+
+    #[dbus_interface(property)]
+    fn phase(&self) -> String {
+        lock(&self.status).phase.to_string()
+    }
+
+    #[dbus_interface(property)]
+    fn running(&self) -> bool {
+        lock(&self.status).running
+    }
+
+    #[dbus_interface(property)]
+    fn paused(&self) -> bool {
+        lock(&self.status).paused
+    }
+
+    #[dbus_interface(property)]
+    fn remaining(&self) -> u64 {
+        lock(&self.status).remaining
+    }
+
+    #[dbus_interface(property)]
+    fn active_task(&self) -> String {
+        lock(&self.status).active_task.clone()
+    }
+}
+
+/// NOTE `org.pomodoro.Timer` on the session bus, so desktop shortcuts and panel applets can
+/// drive the timer. Connected in the background, the app goes on without it when that fails
+pub struct Service {
+    status: Arc<Mutex<TimerStatus>>,
+    connection: Arc<Mutex<Option<Connection>>>,
+}
+
+impl Service {
+    pub fn start(sender: Sender<Event>) -> Self {
+        let status = Arc::new(Mutex::new(TimerStatus::default()));
+        let connection = Arc::new(Mutex::new(None));
+        let interface = TimerInterface {
+            status: status.clone(),
+            sender,
+        };
+        let shared = connection.clone();
+        let spawned = thread::Builder::new()
+            .name("dbus service".to_string())
+            .spawn(move || match connect(interface) {
+                Ok(conn) => {
+                    log::info!("serving {} on session bus", BUS_NAME);
+                    *lock(&shared) = Some(conn);
+                }
+                Err(e) => log::warn!("failed to serve {}: {}", BUS_NAME, e),
+            });
+        if let Err(e) = spawned {
+            log::error!("failed to start dbus service thread: {}", e);
+        }
+        Self { status, connection }
+    }
+
+    /// NOTE changed properties are signalled with `PropertiesChanged`
+    pub fn update(&self, status: TimerStatus) {
+        let changed = {
+            let mut current = lock(&self.status);
+            let changed = status.changes(&current);
+            *current = status;
+            changed
+        };
+        if changed.is_empty() {
+            return;
+        }
+        let connection = lock(&self.connection);
+        let Some(ref conn) = *connection else {
+            return;
+        };
+        let invalidated: Vec<&str> = Vec::new();
+        if let Err(e) = conn.emit_signal(
+            None::<&str>,
+            OBJECT_PATH,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+            &(BUS_NAME, changed, invalidated),
+        ) {
+            log::warn!("failed to signal {} changes: {}", BUS_NAME, e);
+        }
+    }
+}
+
+/// NOTE fails when another pomodoro already has the name
+fn connect(interface: TimerInterface) -> zbus::Result<Connection> {
+    ConnectionBuilder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, interface)?
+        .build()
+}
@@ -0,0 +1,87 @@
+use crate::app::Task;
+use crate::config;
+use crate::history::PomodoroRecord;
+use crate::presets;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// NOTE tasks and settings kept between runs
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedData {
+    pub next_task_id: u64,
+    pub active_task_id: Option<u64>,
+    pub settings: SavedSettings,
+    pub tasks: Vec<Task>,
+    pub archived_tasks: Vec<Task>,
+    // NOTE oldest first
+    pub history: Vec<PomodoroRecord>,
+}
+
+/// NOTE phase lengths in seconds
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedSettings {
+    pub pomodoro: u64,
+    pub short_break: u64,
+    pub long_break: u64,
+    pub pomodoros_per_long_break: u64,
+}
+
+impl SavedSettings {
+    /// NOTE settings are multiplied into durations unchecked, so ones out of the range of the
+    /// settings fields are refused. Zero is a setting never saved, its default is kept
+    pub fn check(&self) -> Result<(), String> {
+        let minutes = |secs: u64| secs.div_ceil(60);
+        [
+            ("pomodoro", minutes(self.pomodoro)),
+            ("short_break", minutes(self.short_break)),
+            ("long_break", minutes(self.long_break)),
+            ("pomodoros_per_long_break", self.pomodoros_per_long_break),
+        ]
+        .into_iter()
+        .filter(|(_, value)| *value > 0)
+        .try_for_each(|(name, value)| presets::check_setting(name, value))
+    }
+}
+
+/// NOTE `$XDG_DATA_HOME/pomodoro/data.toml`
+pub fn data_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join("data.toml"))
+}
+
+/// NOTE None when nothing was saved yet
+pub fn load(path: &Path) -> Result<Option<SavedData>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+    };
+    toml::from_str(&text)
+        .map(Some)
+        .map_err(|e| format!("invalid data file {}: {}", path.display(), e))
+}
+
+pub fn save(path: &Path, data: &SavedData) -> io::Result<()> {
+    let text = toml::to_string(data).map_err(io::Error::other)?;
+    write_file(path, &text)
+}
+
+/// NOTE written next to the old file first, so a crash halfway keeps the old one whole
+pub fn write_file(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, text)?;
+    fs::rename(&tmp, path)
+}
+
+/// NOTE move a file that failed to load out of the way, so saving does not overwrite it
+pub fn set_aside(path: &Path) -> io::Result<PathBuf> {
+    let aside = path.with_extension("toml.broken");
+    fs::rename(path, &aside)?;
+    Ok(aside)
+}
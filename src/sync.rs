@@ -0,0 +1,7 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// NOTE a thread that panicked while holding `mutex` left nothing half done that matters
+/// here, the other threads go on past it
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}